@@ -0,0 +1,235 @@
+use crate::config::{self, Config};
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const APP: &str = "picon";
+
+// bumped when a field changes its meaning, older archives are still read
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RestoreMode {
+    // the settings of this device are kept, the markers and holdings of the archive added
+    #[default]
+    Merge,
+
+    // everything is taken from the archive
+    Replace,
+}
+
+// The user data of one device in a single json file, which is copied between the
+// devices, e.g. from the shared Download directory of Android.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Backup {
+    pub app: String,
+    pub version: u32,
+    pub created_at: i64,
+
+    // `picoin.conf`
+    pub config: Config,
+
+    // `marker_symbols.json`
+    #[serde(default)]
+    pub marker_symbols: Vec<String>,
+
+    // `holdings.json`, coin id -> amount
+    #[serde(default)]
+    pub holdings: HashMap<u64, f64>,
+}
+
+fn marker_path(conf: &Config) -> PathBuf {
+    conf.cache_dir.join("marker_symbols.json")
+}
+
+fn holdings_path(conf: &Config) -> PathBuf {
+    conf.cache_dir.join("holdings.json")
+}
+
+impl Backup {
+    // the files which do not exist yet are empty
+    pub fn collect(conf: &Config) -> Self {
+        Self {
+            app: APP.to_string(),
+            version: VERSION,
+            created_at: util::timestamp(),
+            config: conf.clone(),
+            marker_symbols: cache::load(&marker_path(conf)).unwrap_or_default(),
+            holdings: cache::load(&holdings_path(conf)).unwrap_or_default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.app != APP {
+            return Err(anyhow!("not a picon backup"));
+        }
+
+        if self.version == 0 || self.version > VERSION {
+            return Err(anyhow!(
+                "backup version {} is not supported, update picon to restore it",
+                self.version
+            ));
+        }
+
+        if self.marker_symbols.iter().any(|v| v.trim().is_empty()) {
+            return Err(anyhow!("invalid marker symbol"));
+        }
+
+        if let Some((id, _)) = self
+            .holdings
+            .iter()
+            .find(|(_, v)| !v.is_finite() || **v <= 0.)
+        {
+            return Err(anyhow!("invalid holding of coin {id}"));
+        }
+
+        Ok(())
+    }
+
+    // `{dir}/picon-backup-{timestamp}.json`
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{APP}-backup-{}.json", self.created_at));
+        cache::write_atomic(&path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(path)
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |e| anyhow!("invalid backup {}: {e}", path.display());
+
+        // the config of an older build is migrated like `picoin.conf`
+        let mut value = serde_json::from_str::<Value>(&text).map_err(invalid)?;
+        if let Some(config) = value.get_mut("config") {
+            config::migrate(config)?;
+        }
+        let backup = serde_json::from_value::<Backup>(value).map_err(invalid)?;
+        backup.validate()?;
+        Ok(backup)
+    }
+
+    // validated again, nothing is written when the archive is invalid
+    pub fn restore(self, conf: &mut Config, mode: RestoreMode) -> Result<()> {
+        self.validate()?;
+
+        let (mut markers, mut holdings) = match mode {
+            RestoreMode::Merge => (
+                cache::load::<Vec<String>>(&marker_path(conf)).unwrap_or_default(),
+                cache::load::<HashMap<u64, f64>>(&holdings_path(conf)).unwrap_or_default(),
+            ),
+            RestoreMode::Replace => (vec![], HashMap::new()),
+        };
+
+        markers.extend(self.marker_symbols);
+        let markers = markers.into_iter().collect::<BTreeSet<_>>();
+
+        // the amounts of this device win over the archive
+        for (id, amount) in self.holdings {
+            holdings.entry(id).or_insert(amount);
+        }
+
+        if mode == RestoreMode::Replace {
+            // the export directory is a path of the other device
+            let dir = conf.export.dir.clone();
+            conf.apply(self.config);
+            conf.export.dir = dir;
+        }
+
+        cache::save(&marker_path(conf), &markers)?;
+        cache::save(&holdings_path(conf), &holdings)?;
+        conf.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Locale;
+    use crate::test_support;
+
+    fn conf(dir: &Path) -> Config {
+        Config {
+            config_path: dir.join("picoin.conf"),
+            cache_dir: dir.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    fn backup() -> Backup {
+        let dir = test_support::temp_dir();
+        let mut source = conf(&dir);
+        source.ui.locale = Locale::Ja;
+        cache::save(&marker_path(&source), &["BTC", "SOL"]).unwrap();
+        cache::save(
+            &holdings_path(&source),
+            &HashMap::from([(1, 0.5), (5426, 10.)]),
+        )
+        .unwrap();
+        Backup::collect(&source)
+    }
+
+    #[test]
+    fn write_and_read() {
+        let backup = backup();
+        let dir = test_support::temp_dir();
+        let path = backup.write(&dir).unwrap();
+
+        let read = Backup::read(&path).unwrap();
+        assert_eq!(read.version, VERSION);
+        assert_eq!(read.marker_symbols, ["BTC", "SOL"]);
+        assert_eq!(read.holdings[&5426], 10.);
+        assert_eq!(read.config.ui.locale, Locale::Ja);
+    }
+
+    #[test]
+    fn read_rejects_invalid_archives() {
+        let dir = test_support::temp_dir();
+
+        let mut newer = backup();
+        newer.version = VERSION + 1;
+        assert!(Backup::read(&newer.write(&dir).unwrap()).is_err());
+
+        let mut negative = backup();
+        negative.holdings.insert(2, -1.);
+        assert!(Backup::read(&negative.write(&dir).unwrap()).is_err());
+
+        let path = dir.join("other.json");
+        fs::write(&path, r#"{"app": "other"}"#).unwrap();
+        assert!(Backup::read(&path).is_err());
+    }
+
+    #[test]
+    fn merge_keeps_local_data() {
+        let dir = test_support::temp_dir();
+        let mut target = conf(&dir);
+        cache::save(&marker_path(&target), &["ETH"]).unwrap();
+        cache::save(&holdings_path(&target), &HashMap::from([(1, 2.)])).unwrap();
+
+        backup().restore(&mut target, RestoreMode::Merge).unwrap();
+
+        let markers = cache::load::<Vec<String>>(&marker_path(&target)).unwrap();
+        let holdings = cache::load::<HashMap<u64, f64>>(&holdings_path(&target)).unwrap();
+        assert_eq!(markers, ["BTC", "ETH", "SOL"]);
+        assert_eq!(holdings, HashMap::from([(1, 2.), (5426, 10.)]));
+        assert_eq!(target.ui.locale, Locale::default());
+    }
+
+    #[test]
+    fn replace_takes_the_archive() {
+        let dir = test_support::temp_dir();
+        let mut target = conf(&dir);
+        target.export.dir = "/sdcard/picon".to_string();
+        cache::save(&marker_path(&target), &["ETH"]).unwrap();
+
+        backup().restore(&mut target, RestoreMode::Replace).unwrap();
+
+        let markers = cache::load::<Vec<String>>(&marker_path(&target)).unwrap();
+        assert_eq!(markers, ["BTC", "SOL"]);
+        assert_eq!(target.ui.locale, Locale::Ja);
+        assert_eq!(target.export.dir, "/sdcard/picon");
+        assert!(target.config_path.exists());
+    }
+}
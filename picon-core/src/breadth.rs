@@ -0,0 +1,259 @@
+use crate::history::{History, HOUR_SECS};
+use crate::latest::{Latest, LatestDataItem};
+use std::collections::{BTreeSet, HashMap};
+
+// the upper bounds of the histogram buckets in percent, the last bucket is open
+pub const BUCKET_BOUNDS: [f64; 7] = [-10., -5., -2., 0., 2., 5., 10.];
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    H1,
+    #[default]
+    H24,
+    D7,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::H1, Period::H24, Period::D7];
+
+    pub fn secs(self) -> i64 {
+        match self {
+            Period::H1 => HOUR_SECS,
+            Period::H24 => 24 * HOUR_SECS,
+            Period::D7 => 7 * 24 * HOUR_SECS,
+        }
+    }
+
+    pub fn change(self, item: &LatestDataItem) -> f64 {
+        let value = &item.quote.value;
+        match self {
+            Period::H1 => value.percent_change_1h,
+            Period::H24 => value.percent_change_24h,
+            Period::D7 => value.percent_change_7d,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub advancers: usize,
+    pub decliners: usize,
+    pub unchanged: usize,
+    pub mean: f64,
+    pub median: f64,
+
+    // the count of the changes in each bucket of `BUCKET_BOUNDS`, plus the open bucket
+    pub buckets: [usize; BUCKET_BOUNDS.len() + 1],
+}
+
+impl Summary {
+    pub fn new(latest: &Latest, period: Period) -> Self {
+        let mut changes = changes(latest, period);
+        let mut summary = Self {
+            mean: f64::NAN,
+            median: f64::NAN,
+            ..Default::default()
+        };
+
+        if changes.is_empty() {
+            return summary;
+        }
+
+        for &v in changes.iter() {
+            match v.partial_cmp(&0.) {
+                Some(std::cmp::Ordering::Greater) => summary.advancers += 1,
+                Some(std::cmp::Ordering::Less) => summary.decliners += 1,
+                _ => summary.unchanged += 1,
+            }
+
+            let bucket = BUCKET_BOUNDS
+                .iter()
+                .position(|&bound| v < bound)
+                .unwrap_or(BUCKET_BOUNDS.len());
+            summary.buckets[bucket] += 1;
+        }
+
+        changes.sort_by(f64::total_cmp);
+        let mid = changes.len() / 2;
+        summary.median = if changes.len().is_multiple_of(2) {
+            (changes[mid - 1] + changes[mid]) / 2.
+        } else {
+            changes[mid]
+        };
+        summary.mean = changes.iter().sum::<f64>() / changes.len() as f64;
+
+        summary
+    }
+
+    // advancers / decliners, NaN without decliners
+    pub fn ratio(&self) -> f64 {
+        ratio(self.advancers, self.decliners)
+    }
+}
+
+fn ratio(advancers: usize, decliners: usize) -> f64 {
+    if decliners == 0 {
+        f64::NAN
+    } else {
+        advancers as f64 / decliners as f64
+    }
+}
+
+fn changes(latest: &Latest, period: Period) -> Vec<f64> {
+    latest
+        .data
+        .iter()
+        .map(|v| period.change(v))
+        .filter(|v| v.is_finite())
+        .collect()
+}
+
+// the top `count` gainers and losers, the largest move first
+pub fn movers(
+    latest: &Latest,
+    period: Period,
+    count: usize,
+) -> (Vec<&LatestDataItem>, Vec<&LatestDataItem>) {
+    let mut items = latest
+        .data
+        .iter()
+        .filter(|v| period.change(v).is_finite())
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| period.change(b).total_cmp(&period.change(a)));
+
+    let gainers = items
+        .iter()
+        .take(count)
+        .filter(|v| period.change(v) > 0.)
+        .copied()
+        .collect();
+
+    let losers = items
+        .iter()
+        .rev()
+        .take(count)
+        .filter(|v| period.change(v) < 0.)
+        .copied()
+        .collect();
+
+    (gainers, losers)
+}
+
+// The advance/decline ratio of every recorded hour, comparing each coin with its snapshot
+// `period` earlier. The hours without any comparable snapshot are skipped.
+pub fn advance_decline(history: &History, period: Period) -> Vec<(i64, f64)> {
+    let hours_back = period.secs() / HOUR_SECS;
+    let mut hours = BTreeSet::new();
+    let coins = history
+        .coins
+        .values()
+        .map(|points| {
+            points
+                .iter()
+                .map(|&(ts, price)| {
+                    hours.insert(ts / HOUR_SECS);
+                    (ts / HOUR_SECS, price)
+                })
+                .collect::<HashMap<i64, f64>>()
+        })
+        .collect::<Vec<_>>();
+
+    hours
+        .into_iter()
+        .filter_map(|hour| {
+            let (mut advancers, mut decliners) = (0, 0);
+            for prices in coins.iter() {
+                let (Some(now), Some(before)) =
+                    (prices.get(&hour), prices.get(&(hour - hours_back)))
+                else {
+                    continue;
+                };
+
+                if now > before {
+                    advancers += 1;
+                } else if now < before {
+                    decliners += 1;
+                }
+            }
+
+            let ratio = ratio(advancers, decliners);
+            (advancers + decliners > 0).then_some((hour * HOUR_SECS, ratio))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::latest::{LatestDataItemQuote, LatestDataItemQuoteValue};
+
+    fn latest(changes: &[f64]) -> Latest {
+        let mut latest = Latest::default();
+        for (i, &change) in changes.iter().enumerate() {
+            latest.data.push(LatestDataItem {
+                id: i as u64,
+                symbol: format!("C{i}"),
+                quote: LatestDataItemQuote {
+                    currency: "USD".to_string(),
+                    value: LatestDataItemQuoteValue {
+                        percent_change_24h: change,
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            });
+        }
+        latest
+    }
+
+    #[test]
+    fn summary_of_changes() {
+        let summary = Summary::new(
+            &latest(&[-12., -3., 0., 1., 4., 20., f64::NAN]),
+            Period::H24,
+        );
+        assert_eq!(
+            (summary.advancers, summary.decliners, summary.unchanged),
+            (3, 2, 1)
+        );
+        assert_eq!(summary.buckets, [1, 0, 1, 0, 2, 1, 0, 1]);
+        assert_eq!(summary.median, 0.5);
+        assert_eq!(summary.mean, 10. / 6.);
+        assert_eq!(summary.ratio(), 1.5);
+
+        assert!(Summary::new(&latest(&[]), Period::H24).median.is_nan());
+    }
+
+    #[test]
+    fn top_movers() {
+        let latest = latest(&[-12., -3., 1., 4., 20.]);
+        let (gainers, losers) = movers(&latest, Period::H24, 3);
+        let symbols = |items: Vec<&LatestDataItem>| {
+            items.iter().map(|v| v.symbol.clone()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(symbols(gainers), ["C4", "C3", "C2"]);
+        assert_eq!(symbols(losers), ["C0", "C1"]);
+    }
+
+    #[test]
+    fn advance_decline_from_history() {
+        let start = 1_700_000_000 / HOUR_SECS * HOUR_SECS;
+        let mut history = History::new("USD");
+        history
+            .coins
+            .insert(1, vec![(start, 1.), (start + HOUR_SECS, 2.)]);
+        history
+            .coins
+            .insert(2, vec![(start, 2.), (start + HOUR_SECS, 1.)]);
+        history
+            .coins
+            .insert(3, vec![(start, 2.), (start + HOUR_SECS + 60, 3.)]);
+
+        assert_eq!(
+            advance_decline(&history, Period::H1),
+            vec![(start + HOUR_SECS, 2.)]
+        );
+    }
+}
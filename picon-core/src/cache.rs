@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// how long `Debounced` waits for the writes to settle
+pub const WRITE_DELAY: Duration = Duration::from_secs(1);
+
+// the json files under `Config::cache_dir`, such as `latest.json`
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = fs::read_to_string(path)?;
+    if text.trim().is_empty() {
+        return Err(anyhow!("{}: empty file", path.display()));
+    }
+
+    serde_json::from_str::<T>(&text).map_err(|e| anyhow!("{}: {e}", path.display()))
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    match serde_json::to_string(value) {
+        Ok(text) => write_atomic(path, text.as_bytes()),
+        Err(e) => Err(anyhow!("{e:?}")),
+    }
+}
+
+// writes a sibling temp file, syncs it and renames it over `path`,
+// so a kill mid-write leaves either the old content or the new one
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = tmp_path(path);
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+
+    if let Err(e) = fs::rename(&tmp, path) {
+        _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    // the rename is only durable once the directory entry is synced too
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+// holds the last value written to a file until no other write came for `WRITE_DELAY`
+#[derive(Debug, Clone)]
+pub struct Debounced<T> {
+    pending: Option<(PathBuf, T, Instant)>,
+}
+
+impl<T> Default for Debounced<T> {
+    fn default() -> Self {
+        Self { pending: None }
+    }
+}
+
+impl<T: Serialize> Debounced<T> {
+    pub fn set(&mut self, path: PathBuf, value: T) {
+        self.pending = Some((path, value, Instant::now()));
+    }
+
+    // the time left before the pending value is due, `None` when nothing is pending
+    pub fn due_in(&self) -> Option<Duration> {
+        self.pending
+            .as_ref()
+            .map(|(_, _, at)| WRITE_DELAY.saturating_sub(at.elapsed()))
+    }
+
+    // saves the pending value once it is due
+    pub fn flush_due(&mut self) -> Result<()> {
+        match self.due_in() {
+            Some(d) if d.is_zero() => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        match self.pending.take() {
+            Some((path, value, _)) => save(&path, &value),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+
+    #[test]
+    fn save_replaces_file_without_leftovers() {
+        let dir = temp_dir();
+        let path = dir.join("latest.json");
+        fs::write(&path, "[1,2,3]").unwrap();
+
+        save(&path, &vec![4, 5]).unwrap();
+
+        assert_eq!(load::<Vec<i32>>(&path).unwrap(), vec![4, 5]);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn load_rejects_empty_and_truncated_files() {
+        let dir = temp_dir();
+        let path = dir.join("stats.json");
+
+        fs::write(&path, "  \n").unwrap();
+        assert!(load::<Vec<i32>>(&path).is_err());
+
+        fs::write(&path, "[1,2,").unwrap();
+        assert!(load::<Vec<i32>>(&path).is_err());
+    }
+
+    #[test]
+    fn debounced_saves_last_value_on_flush() {
+        let dir = temp_dir();
+        let path = dir.join("marker_symbols.json");
+        let mut writer = Debounced::default();
+
+        writer.set(path.clone(), vec!["BTC"]);
+        writer.set(path.clone(), vec!["BTC", "ETH"]);
+        writer.flush_due().unwrap();
+        assert!(!path.exists());
+        assert!(writer.due_in().is_some());
+
+        writer.flush().unwrap();
+        assert_eq!(load::<Vec<String>>(&path).unwrap(), vec!["BTC", "ETH"]);
+        assert!(writer.due_in().is_none());
+    }
+}
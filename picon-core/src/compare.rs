@@ -0,0 +1,123 @@
+use crate::history::{DAY_SECS, HOUR_SECS};
+use std::collections::HashMap;
+
+pub const MIN_COINS: usize = 2;
+pub const MAX_COINS: usize = 5;
+
+// the local history is kept for 30 days, see `history::MAX_AGE_SECS`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Range {
+    D1,
+    #[default]
+    D7,
+    D30,
+}
+
+impl Range {
+    pub const ALL: [Range; 3] = [Range::D1, Range::D7, Range::D30];
+
+    pub fn secs(self) -> i64 {
+        match self {
+            Range::D1 => DAY_SECS,
+            Range::D7 => 7 * DAY_SECS,
+            Range::D30 => 30 * DAY_SECS,
+        }
+    }
+}
+
+// the prices as a percentage of the first one, which is 100
+pub fn rebase(points: &[(i64, f64)]) -> Vec<(i64, f64)> {
+    let Some(&(_, first)) = points.iter().find(|p| p.1 > 0.) else {
+        return vec![];
+    };
+
+    points
+        .iter()
+        .filter(|p| p.1 > 0.)
+        .map(|&(ts, price)| (ts, price / first * 100.))
+        .collect()
+}
+
+// The Pearson coefficient of the hourly log returns, over the hours both coins have a
+// snapshot. `None` when there are too few common hours to tell.
+pub fn correlation(a: &[(i64, f64)], b: &[(i64, f64)]) -> Option<f64> {
+    let b = b
+        .iter()
+        .filter(|p| p.1 > 0.)
+        .map(|p| (p.0 / HOUR_SECS, p.1))
+        .collect::<HashMap<_, _>>();
+
+    let common = a
+        .iter()
+        .filter(|p| p.1 > 0.)
+        .filter_map(|p| b.get(&(p.0 / HOUR_SECS)).map(|v| (p.1, *v)))
+        .collect::<Vec<_>>();
+
+    let (x, y): (Vec<f64>, Vec<f64>) = common
+        .windows(2)
+        .map(|w| ((w[1].0 / w[0].0).ln(), (w[1].1 / w[0].1).ln()))
+        .unzip();
+
+    pearson(&x, &y)
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len();
+    if n < 3 || n != y.len() {
+        return None;
+    }
+
+    let (mx, my) = (
+        x.iter().sum::<f64>() / n as f64,
+        y.iter().sum::<f64>() / n as f64,
+    );
+    let (mut cov, mut vx, mut vy) = (0., 0., 0.);
+    for (a, b) in x.iter().zip(y) {
+        cov += (a - mx) * (b - my);
+        vx += (a - mx).powi(2);
+        vy += (b - my).powi(2);
+    }
+
+    // a flat price has no returns to correlate
+    if vx <= 0. || vy <= 0. {
+        return None;
+    }
+    Some((cov / (vx * vy).sqrt()).clamp(-1., 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(prices: &[f64]) -> Vec<(i64, f64)> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i as i64 * HOUR_SECS, *v))
+            .collect()
+    }
+
+    #[test]
+    fn rebase_to_100() {
+        let points = rebase(&series(&[50., 75., 25.]));
+        assert_eq!(
+            points.iter().map(|p| p.1).collect::<Vec<_>>(),
+            [100., 150., 50.]
+        );
+        assert!(rebase(&[]).is_empty());
+    }
+
+    #[test]
+    fn correlation_of_returns() {
+        let a = series(&[100., 110., 99., 120., 118.]);
+        let doubled = series(&[200., 220., 198., 240., 236.]);
+        let inverse = a.iter().map(|p| (p.0, 1. / p.1)).collect::<Vec<_>>();
+
+        assert!((correlation(&a, &doubled).unwrap() - 1.).abs() < 1e-9);
+        assert!((correlation(&a, &inverse).unwrap() + 1.).abs() < 1e-9);
+
+        // the hours only one coin has are skipped
+        assert_eq!(correlation(&a, &series(&[1., 2.])), None);
+        assert_eq!(correlation(&a, &series(&[1.; 5])), None);
+    }
+}
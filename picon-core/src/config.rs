@@ -0,0 +1,849 @@
+use crate::economy::{EconomySource, InstrumentId};
+use crate::export::ExportFormat;
+use crate::fees::Chain;
+use crate::latest::{SortKey, SortSpec};
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::{env, fs};
+
+#[allow(unused_imports)]
+use std::path::PathBuf;
+
+#[cfg(not(target_os = "android"))]
+use platform_dirs::{AppDirs, UserDirs};
+
+#[cfg(target_os = "android")]
+pub struct AppDirs {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+}
+
+#[cfg(target_os = "android")]
+impl AppDirs {
+    pub fn new(name: Option<&str>, _: bool) -> Option<Self> {
+        let root_dir = "/data/data";
+        let name = name.unwrap();
+
+        Some(Self {
+            config_dir: PathBuf::from(&format!("{root_dir}/{name}/config")),
+            data_dir: PathBuf::from(&format!("{root_dir}/{name}/data")),
+        })
+    }
+}
+
+// the version written by this build, see `MIGRATIONS`
+pub const CONFIG_VERSION: u32 = 2;
+
+// `MIGRATIONS[i]` upgrades a config of version `i` to `i + 1`. The files written before
+// the version field are version 0 and can have any of the older layouts.
+const MIGRATIONS: [fn(&mut Value); CONFIG_VERSION as usize] = [
+    // 1: `ui.locale` replaces `ui.is_cn`
+    |value| {
+        let Some(ui) = value.get_mut("ui").and_then(Value::as_object_mut) else {
+            return;
+        };
+
+        if let Some(is_cn) = ui.remove("is_cn") {
+            if !ui.contains_key("locale") {
+                let locale = if is_cn.as_bool().unwrap_or_default() {
+                    Locale::ZhCN
+                } else {
+                    Locale::En
+                };
+                ui.insert("locale".to_string(), json!(locale));
+            }
+        }
+    },
+    // 2: `latest.columns` replaces the `latest.sparkline` switch
+    |value| {
+        let Some(latest) = value.get_mut("latest").and_then(Value::as_object_mut) else {
+            return;
+        };
+
+        if let Some(sparkline) = latest.remove("sparkline") {
+            if !latest.contains_key("columns") {
+                let mut columns = Latest::default().columns;
+                for c in columns.iter_mut() {
+                    if c.column == LatestColumn::Sparkline {
+                        c.is_visible = sparkline.as_bool().unwrap_or(true);
+                    }
+                }
+                latest.insert("columns".to_string(), json!(columns));
+            }
+        }
+    },
+];
+
+// Brings a config of any older version to `CONFIG_VERSION`. A config of a newer build
+// is left as it is, its unknown fields are ignored. Returns the version before the migration.
+pub fn migrate(value: &mut Value) -> Result<u32> {
+    if !value.is_object() {
+        return Err(anyhow!("the config is not an object"));
+    }
+
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    let version = u32::try_from(version).unwrap_or(u32::MAX);
+    if version > CONFIG_VERSION {
+        log::warn!("config version {version} is newer than {CONFIG_VERSION}");
+        return Ok(version);
+    }
+
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(value);
+    }
+    value["version"] = json!(CONFIG_VERSION);
+    Ok(version)
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    #[serde(skip)]
+    pub working_dir: PathBuf,
+
+    #[serde(skip)]
+    pub config_path: PathBuf,
+
+    #[serde(skip)]
+    pub db_path: PathBuf,
+
+    #[serde(skip)]
+    pub cache_dir: PathBuf,
+
+    #[serde(skip)]
+    pub icons_dir: PathBuf,
+
+    // the copy of a config file which could not be read completely, set by `Config::init`
+    #[serde(skip)]
+    pub invalid_backup: Option<PathBuf>,
+
+    pub version: u32,
+
+    pub ui: UI,
+
+    #[serde(default)]
+    pub net: Net,
+
+    #[serde(default)]
+    pub latest: Latest,
+
+    #[serde(default)]
+    pub stats: Stats,
+
+    #[serde(default)]
+    pub fees: Fees,
+
+    #[serde(default)]
+    pub economy: Economy,
+
+    #[serde(default)]
+    pub converter: Converter,
+
+    #[serde(default)]
+    pub export: Export,
+
+    #[serde(default)]
+    pub api: Api,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UI {
+    #[serde(default)]
+    pub locale: Locale,
+
+    #[serde(default)]
+    pub theme: ThemeMode,
+
+    #[serde(default)]
+    pub color_convention: ColorConvention,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[serde(rename = "en")]
+    En,
+
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCN,
+
+    #[serde(rename = "zh-TW")]
+    ZhTW,
+
+    #[serde(rename = "ja")]
+    Ja,
+
+    #[serde(rename = "ko")]
+    Ko,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 5] = [
+        Locale::En,
+        Locale::ZhCN,
+        Locale::ZhTW,
+        Locale::Ja,
+        Locale::Ko,
+    ];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&v| v == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ThemeMode {
+    #[serde(rename = "light")]
+    Light,
+
+    #[serde(rename = "dark")]
+    Dark,
+
+    // follow the light/dark preference of the system
+    #[default]
+    #[serde(rename = "system")]
+    System,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Light, ThemeMode::Dark, ThemeMode::System];
+}
+
+// the colors of rising and falling prices
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorConvention {
+    #[default]
+    #[serde(rename = "green-up")]
+    GreenUp,
+
+    #[serde(rename = "red-up")]
+    RedUp,
+
+    // colorblind safe
+    #[serde(rename = "blue-orange")]
+    BlueOrange,
+}
+
+impl ColorConvention {
+    pub const ALL: [ColorConvention; 3] = [
+        ColorConvention::GreenUp,
+        ColorConvention::RedUp,
+        ColorConvention::BlueOrange,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Net {
+    pub is_offline: bool,
+
+    // seconds after the upstream timestamp before data is shown as stale
+    pub stale_secs: i64,
+}
+
+impl Default for Net {
+    fn default() -> Self {
+        Self {
+            is_offline: false,
+            stale_secs: 15 * 60,
+        }
+    }
+}
+
+pub const COLUMN_WIDTH: f32 = 80.;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Latest {
+    // the `convert` currency of the listings request
+    pub currency: String,
+    pub limit: u32,
+
+    // the columns right of the rank, in display order
+    pub columns: Vec<ColumnLayout>,
+
+    // the primary sort key first
+    pub sort: Vec<SortSpec>,
+
+    pub view: LatestView,
+    pub heatmap_period: HeatmapPeriod,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LatestView {
+    #[default]
+    List,
+    Heatmap,
+}
+
+// the change which colors the heatmap tiles
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeatmapPeriod {
+    #[default]
+    H24,
+    D7,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LatestColumn {
+    Symbol,
+    Price,
+    H1,
+    H24,
+    D7,
+    D30,
+    MarketCap,
+    Volume24h,
+    CirculatingSupply,
+    LocalHighDistance,
+    Holdings,
+    Sparkline,
+}
+
+impl LatestColumn {
+    pub const ALL: [LatestColumn; 12] = [
+        LatestColumn::Symbol,
+        LatestColumn::Price,
+        LatestColumn::H1,
+        LatestColumn::H24,
+        LatestColumn::D7,
+        LatestColumn::D30,
+        LatestColumn::MarketCap,
+        LatestColumn::Volume24h,
+        LatestColumn::CirculatingSupply,
+        LatestColumn::LocalHighDistance,
+        LatestColumn::Holdings,
+        LatestColumn::Sparkline,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct ColumnLayout {
+    pub column: LatestColumn,
+    pub is_visible: bool,
+
+    // the minimum width, the visible columns are widened to fill the panel
+    pub width: f32,
+}
+
+impl Latest {
+    // keeps the saved order and adds the columns unknown to an older config as hidden
+    pub fn normalize_columns(&mut self) {
+        let mut seen = HashSet::new();
+        self.columns.retain(|c| seen.insert(c.column));
+
+        for column in LatestColumn::ALL {
+            if !seen.contains(&column) {
+                self.columns.push(ColumnLayout {
+                    column,
+                    is_visible: false,
+                    width: COLUMN_WIDTH,
+                });
+            }
+        }
+    }
+}
+
+impl Default for Latest {
+    fn default() -> Self {
+        Self {
+            currency: "USD".to_string(),
+            limit: 100,
+            columns: LatestColumn::ALL
+                .into_iter()
+                .map(|column| ColumnLayout {
+                    column,
+                    is_visible: matches!(
+                        column,
+                        LatestColumn::Symbol
+                            | LatestColumn::Price
+                            | LatestColumn::H24
+                            | LatestColumn::D7
+                            | LatestColumn::Sparkline
+                    ),
+                    width: COLUMN_WIDTH,
+                })
+                .collect(),
+            sort: vec![SortSpec::asc(SortKey::Marker)],
+            view: LatestView::default(),
+            heatmap_period: HeatmapPeriod::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Stats {
+    // the days of greed & fear index requested on every fetch
+    pub greed_fear_limit: u32,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            greed_fear_limit: 365,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Economy {
+    pub source: EconomySource,
+
+    // the instruments of the Stats panel, in display order
+    pub instruments: Vec<InstrumentId>,
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Self {
+            source: EconomySource::default(),
+            instruments: vec![
+                InstrumentId::Sse,
+                InstrumentId::Szse,
+                InstrumentId::Chinext,
+                InstrumentId::Dxy,
+                InstrumentId::Us10y,
+                InstrumentId::Spx,
+                InstrumentId::UsdCnh,
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Converter {
+    // a coin symbol or a quote currency
+    pub from: String,
+
+    // one row of the converter panel each
+    pub targets: Vec<String>,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self {
+            from: "BTC".to_string(),
+            targets: vec!["USD".to_string()],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Export {
+    pub format: ExportFormat,
+
+    // the values as shown in the list, otherwise the raw numbers
+    pub is_formatted: bool,
+
+    // the days of price history exported with the list, 0 for none
+    pub history_days: u32,
+
+    // the shared directory of the exports on Android, where the save dialog opens elsewhere.
+    // Empty for `Config::default_export_dir`
+    pub dir: String,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            is_formatted: true,
+            history_days: 0,
+            dir: String::default(),
+        }
+    }
+}
+
+// the JSON-RPC endpoints queried for the fees, in display order
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Fees {
+    pub endpoints: Vec<FeeEndpoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeeEndpoint {
+    pub chain: Chain,
+    pub url: String,
+}
+
+impl Default for Fees {
+    fn default() -> Self {
+        let url = |chain| match chain {
+            Chain::Ethereum => "https://ethereum-rpc.publicnode.com",
+            Chain::Arbitrum => "https://arb1.arbitrum.io/rpc",
+            Chain::Optimism => "https://mainnet.optimism.io",
+            Chain::Base => "https://mainnet.base.org",
+            Chain::Bsc => "https://bsc-dataseed.bnbchain.org",
+            Chain::Polygon => "https://polygon-rpc.com",
+            Chain::Solana => "https://api.mainnet-beta.solana.com",
+        };
+
+        Self {
+            endpoints: Chain::ALL
+                .into_iter()
+                .map(|chain| FeeEndpoint {
+                    chain,
+                    url: url(chain).to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// base urls of the upstreams, can point to a mirror or a local stub
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Api {
+    pub cmc: String,
+    pub apisvr: String,
+
+    // the coin logos, `{cmc_logo}/{id}.png`
+    pub cmc_logo: String,
+
+    // the coin pages, `{cmc_site}/currencies/{slug}/`
+    pub cmc_site: String,
+
+    // the alternative.me greed & fear index, `{greed_fear}/fng/`
+    pub greed_fear: String,
+
+    // the Yahoo Finance chart api, `{yahoo}/v8/finance/chart/{symbol}`
+    pub yahoo: String,
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Self {
+            cmc: "https://pro-api.coinmarketcap.com".to_string(),
+            apisvr: "https://heng30.xyz/apisvr".to_string(),
+            cmc_logo: "https://s2.coinmarketcap.com/static/img/coins/64x64".to_string(),
+            cmc_site: "https://coinmarketcap.com".to_string(),
+            greed_fear: "https://api.alternative.me".to_string(),
+            yahoo: "https://query1.finance.yahoo.com".to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub fn init(&mut self) -> Result<()> {
+        let app_name = if cfg!(not(target_os = "android")) {
+            "picon"
+        } else {
+            "xyz.heng30.picon"
+        };
+
+        let app_dirs = AppDirs::new(Some(app_name), true).unwrap();
+
+        self.init_app_dir(&app_dirs)?;
+        self.load()?;
+        log::debug!("{:?}", self);
+        Ok(())
+    }
+
+    fn init_app_dir(&mut self, app_dirs: &AppDirs) -> Result<()> {
+        self.config_path = app_dirs.config_dir.join("picoin.conf");
+        self.db_path = app_dirs.data_dir.join("picoin.db");
+
+        self.cache_dir = app_dirs.data_dir.join("cache");
+        self.icons_dir = self.cache_dir.join("icons");
+        self.working_dir = {
+            let mut dir = env::current_exe()?;
+            dir.pop();
+            dir
+        };
+
+        fs::create_dir_all(&app_dirs.config_dir)?;
+        fs::create_dir_all(&app_dirs.data_dir)?;
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::create_dir_all(&self.icons_dir)?;
+
+        Ok(())
+    }
+
+    // the settings of `c`, the directories of this device are kept
+    pub fn apply(&mut self, c: Config) {
+        self.ui = c.ui;
+        self.net = c.net;
+        self.latest = c.latest;
+        self.latest.normalize_columns();
+        self.stats = c.stats;
+        self.fees = c.fees;
+        self.economy = c.economy;
+        self.converter = c.converter;
+        self.export = c.export;
+        self.api = c.api;
+    }
+
+    // Migrated to `CONFIG_VERSION` and returned with the version of the file. The sections
+    // which do not parse are left at their defaults and returned by name, so one bad value
+    // does not reset every setting.
+    pub fn parse(text: &str) -> Result<(Config, Vec<String>, u32)> {
+        let mut value = serde_json::from_str::<Value>(text)?;
+        let file_version = migrate(&mut value)?;
+
+        let mut invalid = vec![];
+        if let Value::Object(sections) = &mut value {
+            let keys = sections.keys().cloned().collect::<Vec<_>>();
+            for key in keys {
+                let section = json!({ &key: sections[&key] });
+                if let Err(e) = serde_json::from_value::<Config>(section) {
+                    log::warn!("config section {key}: {e}");
+                    sections.remove(&key);
+                    invalid.push(key);
+                }
+            }
+        }
+
+        let config = serde_json::from_value::<Config>(value)?;
+        Ok((config, invalid, file_version))
+    }
+
+    // an unreadable file is kept next to the config before the defaults replace it
+    fn load(&mut self) -> Result<()> {
+        self.version = CONFIG_VERSION;
+        let Ok(text) = fs::read_to_string(&self.config_path) else {
+            return self.save();
+        };
+
+        match Config::parse(&text) {
+            Ok((c, invalid, file_version)) => {
+                self.apply(c);
+
+                if !invalid.is_empty() {
+                    self.backup_invalid(&text)?;
+                }
+                let is_saved = match file_version.cmp(&CONFIG_VERSION) {
+                    Ordering::Less => true,
+                    Ordering::Equal => !invalid.is_empty(),
+                    // a later save drops the fields unknown here, so the file of a newer build
+                    // is copied first
+                    Ordering::Greater => {
+                        self.backup_newer(&text, file_version)?;
+                        false
+                    }
+                };
+                if is_saved {
+                    self.save()?;
+                }
+            }
+            Err(e) => {
+                log::warn!("{e:?}");
+                self.backup_invalid(&text)?;
+                self.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn backup_invalid(&mut self, text: &str) -> Result<()> {
+        let path = self
+            .config_path
+            .with_extension(format!("conf.invalid-{}", util::timestamp()));
+        cache::write_atomic(&path, text.as_bytes())?;
+        self.invalid_backup = Some(path);
+        Ok(())
+    }
+
+    fn backup_newer(&self, text: &str, file_version: u32) -> Result<()> {
+        let path = self
+            .config_path
+            .with_extension(format!("conf.v{file_version}"));
+        cache::write_atomic(&path, text.as_bytes())?;
+        log::warn!(
+            "config version {file_version} is newer than {CONFIG_VERSION}, kept at {}",
+            path.display()
+        );
+        Ok(())
+    }
+
+    // the downloads directory, shared with the other apps on Android
+    pub fn default_export_dir(&self) -> PathBuf {
+        if cfg!(target_os = "android") {
+            return PathBuf::from("/storage/emulated/0/Download/picon");
+        }
+
+        #[cfg(not(target_os = "android"))]
+        if let Some(dirs) = UserDirs::new() {
+            return dirs.download_dir;
+        }
+
+        self.working_dir.join("export")
+    }
+
+    pub fn export_dir(&self) -> PathBuf {
+        match self.export.dir.trim() {
+            "" => self.default_export_dir(),
+            dir => PathBuf::from(dir),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut value = serde_json::to_value(self)?;
+        value["version"] = json!(CONFIG_VERSION);
+
+        match serde_json::to_string_pretty(&value) {
+            Ok(text) => cache::write_atomic(&self.config_path, text.as_bytes()),
+            Err(e) => Err(anyhow!("{e:?}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn parse(text: &str) -> Config {
+        let (mut conf, invalid, _) = Config::parse(text).unwrap();
+        assert!(invalid.is_empty(), "{invalid:?}");
+        conf.latest.normalize_columns();
+        conf
+    }
+
+    fn column(conf: &Config, column: LatestColumn) -> ColumnLayout {
+        *conf
+            .latest
+            .columns
+            .iter()
+            .find(|c| c.column == column)
+            .unwrap()
+    }
+
+    #[test]
+    fn load_is_cn_format() {
+        let conf = parse(include_str!("../tests/fixtures/config_v0_is_cn.json"));
+        assert_eq!(conf.version, CONFIG_VERSION);
+        assert_eq!(conf.ui.locale, Locale::En);
+        assert!(conf.net.is_offline);
+        assert_eq!(conf.net.stale_secs, Net::default().stale_secs);
+        assert_eq!(conf.latest.currency, "EUR");
+        assert_eq!(conf.latest.limit, 200);
+        assert_eq!(conf.latest.columns.len(), LatestColumn::ALL.len());
+    }
+
+    #[test]
+    fn load_sparkline_format() {
+        let conf = parse(include_str!("../tests/fixtures/config_v0_sparkline.json"));
+        assert_eq!(conf.ui.locale, Locale::Ja);
+        assert_eq!(conf.ui.theme, ThemeMode::Dark);
+        assert_eq!(conf.ui.color_convention, ColorConvention::RedUp);
+        assert_eq!(conf.net.stale_secs, 600);
+        assert!(!column(&conf, LatestColumn::Sparkline).is_visible);
+        assert!(column(&conf, LatestColumn::Price).is_visible);
+    }
+
+    #[test]
+    fn load_columns_format() {
+        let conf = parse(include_str!("../tests/fixtures/config_v0_columns.json"));
+        assert_eq!(conf.ui.locale, Locale::Ko);
+        assert_eq!(conf.latest.columns[0].column, LatestColumn::Price);
+        assert_eq!(conf.latest.columns[0].width, 120.);
+        assert!(!column(&conf, LatestColumn::H24).is_visible);
+        assert_eq!(conf.latest.sort[0].key, SortKey::MarketCap);
+        assert_eq!(conf.latest.view, LatestView::Heatmap);
+        assert_eq!(conf.stats.greed_fear_limit, 90);
+        assert_eq!(conf.economy.source, EconomySource::Yahoo);
+        assert_eq!(
+            conf.economy.instruments,
+            [InstrumentId::Gold, InstrumentId::Spx]
+        );
+    }
+
+    #[test]
+    fn current_format_round_trips() {
+        let dir = test_support::temp_dir();
+        let mut conf = Config {
+            config_path: dir.join("picoin.conf"),
+            ..Default::default()
+        };
+        conf.ui.locale = Locale::ZhTW;
+        conf.latest.limit = 500;
+        conf.save().unwrap();
+
+        let conf = parse(&fs::read_to_string(&conf.config_path).unwrap());
+        assert_eq!(conf.version, CONFIG_VERSION);
+        assert_eq!(conf.ui.locale, Locale::ZhTW);
+        assert_eq!(conf.latest.limit, 500);
+    }
+
+    #[test]
+    fn invalid_section_keeps_the_others() {
+        let text = include_str!("../tests/fixtures/config_invalid_section.json");
+        let (conf, invalid, _) = Config::parse(text).unwrap();
+        assert_eq!(invalid, ["latest"]);
+        assert_eq!(conf.ui.locale, Locale::ZhTW);
+        assert_eq!(conf.latest.limit, Latest::default().limit);
+    }
+
+    #[test]
+    fn unreadable_file_is_backed_up() {
+        let dir = test_support::temp_dir();
+        let mut conf = Config {
+            config_path: dir.join("picoin.conf"),
+            ..Default::default()
+        };
+        fs::write(&conf.config_path, "{\"ui\": ").unwrap();
+
+        conf.load().unwrap();
+
+        let backup = conf.invalid_backup.clone().unwrap();
+        assert_eq!(fs::read_to_string(backup).unwrap(), "{\"ui\": ");
+        let (saved, _, _) = Config::parse(&fs::read_to_string(&conf.config_path).unwrap()).unwrap();
+        assert_eq!(saved.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn outdated_file_is_saved_migrated() {
+        let dir = test_support::temp_dir();
+        let mut conf = Config {
+            config_path: dir.join("picoin.conf"),
+            ..Default::default()
+        };
+        let text = include_str!("../tests/fixtures/config_v0_sparkline.json");
+        fs::write(&conf.config_path, text).unwrap();
+
+        conf.load().unwrap();
+
+        let saved = fs::read_to_string(&conf.config_path).unwrap();
+        let saved = serde_json::from_str::<Value>(&saved).unwrap();
+        assert_eq!(saved["version"], CONFIG_VERSION);
+        assert!(saved["latest"].get("sparkline").is_none());
+        assert!(conf.invalid_backup.is_none());
+    }
+
+    #[test]
+    fn newer_file_is_kept_by_a_save() {
+        let dir = test_support::temp_dir();
+        let mut conf = Config {
+            config_path: dir.join("picoin.conf"),
+            ..Default::default()
+        };
+        let text = r#"{"version": 3, "ui": {"locale": "ja"}, "future": {"is_on": true}}"#;
+        fs::write(&conf.config_path, text).unwrap();
+
+        conf.load().unwrap();
+
+        assert_eq!(conf.ui.locale, Locale::Ja);
+        assert_eq!(fs::read_to_string(&conf.config_path).unwrap(), text);
+
+        // a settings change later on
+        conf.ui.locale = Locale::Ko;
+        conf.save().unwrap();
+        let backup = fs::read_to_string(dir.join("picoin.conf.v3")).unwrap();
+        assert_eq!(backup, text);
+    }
+}
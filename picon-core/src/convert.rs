@@ -0,0 +1,292 @@
+use crate::latest::{self, Latest};
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+// 1 `from` is `rate` `to`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Rate {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+
+    // the upstream time of the prices
+    pub timestamp: i64,
+}
+
+impl Rate {
+    pub fn inverse(&self) -> Rate {
+        Rate {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            rate: 1. / self.rate,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+// The rates of the pairs the listings do not have, from the price-conversion api.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Rates {
+    #[serde(skip)]
+    pub errors: Vec<String>,
+
+    #[serde(skip)]
+    pub is_offline: bool,
+
+    pub items: Vec<Rate>,
+}
+
+impl Rates {
+    // the pairs of `other` replace the same pairs
+    pub fn merge(&mut self, other: Rates) {
+        for item in other.items {
+            match self
+                .items
+                .iter_mut()
+                .find(|v| v.from == item.from && v.to == item.to)
+            {
+                Some(v) => *v = item,
+                None => self.items.push(item),
+            }
+        }
+    }
+
+    // a fetched pair is also used the other way round
+    pub fn get(&self, from: &str, to: &str) -> Option<Rate> {
+        self.items.iter().find_map(|v| {
+            if v.from == from && v.to == to {
+                Some(v.clone())
+            } else if v.from == to && v.to == from {
+                Some(v.inverse())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+// The rate from the prices of the listings, which are all quoted in one currency, so
+// any two of the coins and that currency convert without a request.
+pub fn rate_from_latest(latest: &Latest, from: &str, to: &str) -> Option<Rate> {
+    let currency = &latest.data.first()?.quote.currency;
+    let price = |symbol: &str| {
+        if symbol == currency {
+            return Some(1.);
+        }
+
+        latest
+            .data
+            .iter()
+            .find(|v| v.symbol == symbol)
+            .map(|v| v.quote.value.price)
+            .filter(|v| *v > 0.)
+    };
+
+    Some(Rate {
+        from: from.to_string(),
+        to: to.to_string(),
+        rate: price(from)? / price(to)?,
+        timestamp: util::utc_seconds_from_str(&latest.status.timestamp).unwrap_or_default(),
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct Resp {
+    status: latest::LatestStatus,
+
+    #[serde(default)]
+    data: Option<RespData>,
+}
+
+// a list when queried by symbol, as several coins can share one
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum RespData {
+    One(RespItem),
+    Many(Vec<RespItem>),
+}
+
+#[derive(Deserialize, Debug)]
+struct RespItem {
+    quote: HashMap<String, RespQuote>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RespQuote {
+    price: Option<f64>,
+    last_updated: String,
+}
+
+// Every target is requested on its own, as the basic plan allows one `convert` per
+// request. The failed targets are reported in `Rates::errors`.
+pub async fn fetch(
+    client: &reqwest::Client,
+    api_base: &str,
+    api_key: &str,
+    from: &str,
+    targets: &[String],
+    save_path: &Path,
+) -> Result<Rates> {
+    let mut rates = Rates::default();
+
+    for to in targets {
+        match fetch_rate(client, api_base, api_key, from, to).await {
+            Ok(rate) => rates.items.push(rate),
+            Err(e) => {
+                rates.is_offline |= util::is_network_error(&e);
+                rates
+                    .errors
+                    .push(format!("convert {from} to {to} error: {e:?}"));
+            }
+        }
+    }
+
+    if rates.errors.is_empty() {
+        let mut saved = cache::load::<Rates>(save_path).unwrap_or_default();
+        saved.merge(rates.clone());
+        _ = cache::save(save_path, &saved);
+    }
+
+    Ok(rates)
+}
+
+// curl -H "X-CMC_PRO_API_KEY: $API_KEY" -H "Accept: application/json" -d "amount=1&symbol=BTC&convert=CNY" -G https://pro-api.coinmarketcap.com/v2/tools/price-conversion
+async fn fetch_rate(
+    client: &reqwest::Client,
+    api_base: &str,
+    api_key: &str,
+    from: &str,
+    to: &str,
+) -> Result<Rate> {
+    let resp = client
+        .get(format!("{api_base}/v2/tools/price-conversion"))
+        .headers(latest::headers(api_key))
+        .query(&[("amount", "1"), ("symbol", from), ("convert", to)])
+        .send()
+        .await?
+        .json::<Resp>()
+        .await?;
+
+    if let Some(e) = resp.status.error_message {
+        return Err(anyhow!(e));
+    }
+
+    let item = match resp.data {
+        Some(RespData::One(item)) => item,
+        Some(RespData::Many(items)) => items
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("unknown symbol {from}"))?,
+        None => return Err(anyhow!("no conversion of {from}")),
+    };
+
+    let quote = item
+        .quote
+        .get(to)
+        .ok_or_else(|| anyhow!("no {to} quote of {from}"))?;
+
+    Ok(Rate {
+        from: from.to_string(),
+        to: to.to_string(),
+        rate: quote
+            .price
+            .ok_or_else(|| anyhow!("no {to} price of {from}"))?,
+        timestamp: util::utc_seconds_from_str(&quote.last_updated).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    #[test]
+    fn rate_of_listed_coins() {
+        let latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+
+        let rate = rate_from_latest(&latest, "BTC", "ETH").unwrap();
+        assert!((rate.rate - 61927.344528836584 / 3416.6167649409726).abs() < 1e-9);
+        assert!(rate.timestamp > 0);
+
+        assert_eq!(rate_from_latest(&latest, "USD", "USD").unwrap().rate, 1.);
+        assert_eq!(
+            rate_from_latest(&latest, "BTC", "USD").unwrap().rate,
+            61927.344528836584
+        );
+        assert_eq!(rate_from_latest(&latest, "BTC", "CNY"), None);
+    }
+
+    #[test]
+    fn fetched_rate_is_used_both_ways() {
+        let mut rates = Rates::default();
+        rates.merge(Rates {
+            items: vec![Rate {
+                from: "BTC".to_string(),
+                to: "CNY".to_string(),
+                rate: 4.,
+                timestamp: 1,
+            }],
+            ..Default::default()
+        });
+
+        assert_eq!(rates.get("BTC", "CNY").unwrap().rate, 4.);
+        assert_eq!(rates.get("CNY", "BTC").unwrap().rate, 0.25);
+        assert_eq!(rates.get("BTC", "EUR"), None);
+    }
+
+    #[tokio::test]
+    async fn fetch_recorded_conversion() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("rates.json");
+
+        let rates = fetch(
+            &reqwest::Client::new(),
+            &server.cmc_url(),
+            "test-key",
+            "BTC",
+            &["CNY".to_string()],
+            &save_path,
+        )
+        .await
+        .unwrap();
+        assert!(rates.errors.is_empty());
+        assert_eq!(rates.items[0].rate, 445661.8451603614);
+        assert!(rates.items[0].timestamp > 0);
+        assert!(save_path.exists());
+
+        let req = &server.requests()[0];
+        assert_eq!(req.path, test_support::PRICE_CONVERSION_PATH);
+        assert!(req.query.contains("symbol=BTC"));
+        assert!(req.query.contains("convert=CNY"));
+    }
+
+    #[tokio::test]
+    async fn fetch_reports_failed_targets() {
+        let server = MockServer::start();
+        server.route(
+            test_support::PRICE_CONVERSION_PATH,
+            401,
+            test_support::CMC_LISTINGS_ERROR,
+        );
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("rates.json");
+
+        let rates = fetch(
+            &reqwest::Client::new(),
+            &server.cmc_url(),
+            "test-key",
+            "BTC",
+            &["CNY".to_string()],
+            &save_path,
+        )
+        .await
+        .unwrap();
+        assert!(rates.items.is_empty());
+        assert_eq!(rates.errors.len(), 1);
+        assert!(!rates.is_offline);
+        assert!(!save_path.exists());
+    }
+}
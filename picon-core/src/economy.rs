@@ -0,0 +1,440 @@
+use crate::history::DAY_SECS;
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::task::JoinSet;
+
+// the daily points older than this are dropped
+pub const MAX_AGE_SECS: i64 = 365 * DAY_SECS;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstrumentKind {
+    Index,
+    Fx,
+    Commodity,
+    Yield,
+}
+
+impl InstrumentKind {
+    pub const ALL: [InstrumentKind; 4] = [
+        InstrumentKind::Index,
+        InstrumentKind::Fx,
+        InstrumentKind::Commodity,
+        InstrumentKind::Yield,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentId {
+    Sse,
+    Szse,
+    Chinext,
+    Hsi,
+    N225,
+    Spx,
+    Ndx,
+    Dji,
+    Dxy,
+    UsdCnh,
+    EurUsd,
+    UsdJpy,
+    Gold,
+    Wti,
+    Brent,
+    Us5y,
+    Us10y,
+}
+
+impl InstrumentId {
+    pub const ALL: [InstrumentId; 17] = [
+        InstrumentId::Sse,
+        InstrumentId::Szse,
+        InstrumentId::Chinext,
+        InstrumentId::Hsi,
+        InstrumentId::N225,
+        InstrumentId::Spx,
+        InstrumentId::Ndx,
+        InstrumentId::Dji,
+        InstrumentId::Dxy,
+        InstrumentId::UsdCnh,
+        InstrumentId::EurUsd,
+        InstrumentId::UsdJpy,
+        InstrumentId::Gold,
+        InstrumentId::Wti,
+        InstrumentId::Brent,
+        InstrumentId::Us5y,
+        InstrumentId::Us10y,
+    ];
+
+    pub fn kind(self) -> InstrumentKind {
+        match self {
+            InstrumentId::Sse
+            | InstrumentId::Szse
+            | InstrumentId::Chinext
+            | InstrumentId::Hsi
+            | InstrumentId::N225
+            | InstrumentId::Spx
+            | InstrumentId::Ndx
+            | InstrumentId::Dji
+            | InstrumentId::Dxy => InstrumentKind::Index,
+            InstrumentId::UsdCnh | InstrumentId::EurUsd | InstrumentId::UsdJpy => {
+                InstrumentKind::Fx
+            }
+            InstrumentId::Gold | InstrumentId::Wti | InstrumentId::Brent => {
+                InstrumentKind::Commodity
+            }
+            InstrumentId::Us5y | InstrumentId::Us10y => InstrumentKind::Yield,
+        }
+    }
+
+    // the symbol of the Yahoo Finance chart api
+    pub fn yahoo_symbol(self) -> &'static str {
+        match self {
+            InstrumentId::Sse => "000001.SS",
+            InstrumentId::Szse => "399001.SZ",
+            InstrumentId::Chinext => "399006.SZ",
+            InstrumentId::Hsi => "^HSI",
+            InstrumentId::N225 => "^N225",
+            InstrumentId::Spx => "^GSPC",
+            InstrumentId::Ndx => "^NDX",
+            InstrumentId::Dji => "^DJI",
+            InstrumentId::Dxy => "DX-Y.NYB",
+            InstrumentId::UsdCnh => "CNH=X",
+            InstrumentId::EurUsd => "EURUSD=X",
+            InstrumentId::UsdJpy => "JPY=X",
+            InstrumentId::Gold => "GC=F",
+            InstrumentId::Wti => "CL=F",
+            InstrumentId::Brent => "BZ=F",
+            InstrumentId::Us5y => "^FVX",
+            InstrumentId::Us10y => "^TNX",
+        }
+    }
+
+    // the display names of the apisvr `/market/latest` entries, `None` when apisvr has no entry
+    pub fn apisvr_name(self) -> Option<&'static str> {
+        match self {
+            InstrumentId::Sse => Some("上证指数"),
+            InstrumentId::Szse => Some("深证成指"),
+            InstrumentId::Chinext => Some("创业板指"),
+            InstrumentId::Dxy => Some("美元指数"),
+            InstrumentId::Us10y => Some("美国10年期国债收益率"),
+            InstrumentId::Spx => Some("标普500"),
+            InstrumentId::UsdCnh => Some("离岸人民币"),
+            _ => None,
+        }
+    }
+
+    pub fn from_apisvr_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|id| id.apisvr_name() == Some(name))
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EconomySource {
+    // only serves the instruments with an `InstrumentId::apisvr_name`
+    #[default]
+    Apisvr,
+    Yahoo,
+}
+
+impl EconomySource {
+    pub const ALL: [EconomySource; 2] = [EconomySource::Apisvr, EconomySource::Yahoo];
+
+    pub fn serves(self, id: InstrumentId) -> bool {
+        match self {
+            EconomySource::Apisvr => id.apisvr_name().is_some(),
+            EconomySource::Yahoo => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub id: InstrumentId,
+    pub value: f64,
+    pub percent: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Economy {
+    #[serde(skip)]
+    pub errors: Vec<String>,
+
+    // at least one instrument could not be reached
+    #[serde(skip)]
+    pub is_offline: bool,
+
+    // in the order of the requested instruments
+    pub quotes: Vec<Quote>,
+
+    // the time of the fetch, the sources have no common data time
+    pub timestamp: i64,
+}
+
+impl Economy {
+    // the instruments of `other` replace the same ones, the failed ones keep their last quote
+    pub fn merge(&mut self, other: &Economy) {
+        for quote in other.quotes.iter() {
+            match self.quotes.iter_mut().find(|v| v.id == quote.id) {
+                Some(v) => *v = quote.clone(),
+                None => self.quotes.push(quote.clone()),
+            }
+        }
+        self.timestamp = other.timestamp;
+    }
+}
+
+// the entries of the apisvr `/market/latest`
+#[derive(Deserialize, Debug)]
+struct ApisvrMarket {
+    name: String,
+    value: f64,
+    precent: f64,
+}
+
+// Daily values of the instruments, the last value of a day replaces the earlier ones.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct EconomyHistory {
+    // (utc seconds, value) in time order
+    #[serde(default)]
+    pub instruments: HashMap<InstrumentId, Vec<(i64, f64)>>,
+}
+
+impl EconomyHistory {
+    pub fn record(&mut self, economy: &Economy) {
+        let now = economy.timestamp;
+        if now <= 0 {
+            return;
+        }
+
+        for quote in economy.quotes.iter().filter(|v| v.value.is_finite()) {
+            let points = self.instruments.entry(quote.id).or_default();
+            match points.last_mut() {
+                Some(last) if now < last.0 => continue,
+                Some(last) if now / DAY_SECS == last.0 / DAY_SECS => *last = (now, quote.value),
+                _ => points.push((now, quote.value)),
+            }
+        }
+
+        for points in self.instruments.values_mut() {
+            points.retain(|p| now - p.0 <= MAX_AGE_SECS);
+        }
+        self.instruments.retain(|_, v| !v.is_empty());
+    }
+
+    pub fn series(&self, id: InstrumentId) -> &[(i64, f64)] {
+        self.instruments.get(&id).map_or(&[], |v| v.as_slice())
+    }
+}
+
+pub async fn fetch(
+    client: &reqwest::Client,
+    source: EconomySource,
+    api_base: &str,
+    instruments: &[InstrumentId],
+    save_path: &Path,
+) -> Result<Economy> {
+    let mut economy = match source {
+        EconomySource::Apisvr => fetch_apisvr(client, api_base, instruments).await?,
+        EconomySource::Yahoo => fetch_yahoo(client, api_base, instruments).await?,
+    };
+    economy.timestamp = util::timestamp();
+
+    if economy.errors.is_empty() {
+        _ = cache::save(save_path, &economy);
+    }
+
+    Ok(economy)
+}
+
+async fn fetch_apisvr(
+    client: &reqwest::Client,
+    api_base: &str,
+    instruments: &[InstrumentId],
+) -> Result<Economy> {
+    let markets = client
+        .get(format!("{api_base}/market/latest"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<ApisvrMarket>>()
+        .await?;
+
+    let quotes = markets
+        .into_iter()
+        .filter_map(|v| {
+            let id = InstrumentId::from_apisvr_name(&v.name);
+            if id.is_none() {
+                log::debug!("unknown apisvr market: {}", v.name);
+            }
+
+            Some(Quote {
+                id: id?,
+                value: v.value,
+                percent: v.precent,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Economy {
+        quotes: instruments
+            .iter()
+            .filter_map(|id| quotes.iter().find(|v| v.id == *id).cloned())
+            .collect(),
+        ..Default::default()
+    })
+}
+
+// one chart request per instrument, the failed ones are reported in `Economy::errors`
+async fn fetch_yahoo(
+    client: &reqwest::Client,
+    api_base: &str,
+    instruments: &[InstrumentId],
+) -> Result<Economy> {
+    // the set aborts the requests still running when this fetch is cancelled
+    let mut tasks = JoinSet::new();
+    for (i, &id) in instruments.iter().enumerate() {
+        let client = client.clone();
+        let url = format!("{api_base}/v8/finance/chart/{}", id.yahoo_symbol());
+        tasks.spawn(async move { (i, id, fetch_yahoo_quote(&client, &url, id).await) });
+    }
+
+    let mut results = Vec::with_capacity(instruments.len());
+    while let Some(result) = tasks.join_next().await {
+        results.push(result?);
+    }
+    results.sort_by_key(|v| v.0);
+
+    let mut economy = Economy::default();
+    for result in results {
+        match result {
+            (_, _, Ok(quote)) => economy.quotes.push(quote),
+            (_, id, Err(e)) => {
+                economy.is_offline |= util::is_network_error(&e);
+                economy.errors.push(format!("fetch {id:?} error: {e:?}"));
+            }
+        }
+    }
+
+    Ok(economy)
+}
+
+async fn fetch_yahoo_quote(client: &reqwest::Client, url: &str, id: InstrumentId) -> Result<Quote> {
+    let resp = client
+        .get(url)
+        .query(&[("range", "1d"), ("interval", "1d")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+
+    // the close of the previous session, `chartPreviousClose` is the one before the range
+    let meta = &resp["chart"]["result"][0]["meta"];
+    let (Some(value), Some(prev)) = (
+        meta["regularMarketPrice"].as_f64(),
+        meta["previousClose"].as_f64(),
+    ) else {
+        return Err(anyhow!("invalid chart: {}", resp["chart"]["error"]));
+    };
+
+    Ok(Quote {
+        id,
+        value,
+        percent: (value - prev) / prev * 100.,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    #[tokio::test]
+    async fn fetch_apisvr_instruments() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("economy.json");
+        let instruments = [InstrumentId::Spx, InstrumentId::Gold, InstrumentId::Sse];
+
+        let economy = fetch(
+            &reqwest::Client::new(),
+            EconomySource::Apisvr,
+            &server.apisvr_url(),
+            &instruments,
+            &save_path,
+        )
+        .await
+        .unwrap();
+
+        // gold is not served by apisvr
+        assert!(!EconomySource::Apisvr.serves(InstrumentId::Gold));
+        let ids = economy.quotes.iter().map(|v| v.id).collect::<Vec<_>>();
+        assert_eq!(ids, [InstrumentId::Spx, InstrumentId::Sse]);
+        assert_eq!(economy.quotes[0].value, 5137.08);
+        assert!(economy.timestamp > 0);
+        assert!(save_path.exists());
+    }
+
+    #[tokio::test]
+    async fn fetch_yahoo_instruments() {
+        let server = MockServer::start();
+        server.route(
+            "/v8/finance/chart/GC=F",
+            200,
+            test_support::YAHOO_CHART_GOLD,
+        );
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("economy.json");
+
+        let economy = fetch(
+            &reqwest::Client::new(),
+            EconomySource::Yahoo,
+            &server.cmc_url(),
+            &[InstrumentId::Gold, InstrumentId::Wti],
+            &save_path,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(economy.quotes.len(), 1);
+        assert_eq!(economy.quotes[0].value, 2100.);
+        assert_eq!(economy.quotes[0].percent, 5.);
+        assert_eq!(economy.errors.len(), 1);
+        assert!(server.requests()[0].query.contains("range=1d"));
+        assert!(!save_path.exists());
+    }
+
+    #[test]
+    fn record_keeps_one_value_per_day() {
+        let start = 1_700_000_000 / DAY_SECS * DAY_SECS;
+        let economy = |timestamp, value| Economy {
+            quotes: vec![Quote {
+                id: InstrumentId::Gold,
+                value,
+                percent: 0.,
+            }],
+            timestamp,
+            ..Default::default()
+        };
+
+        let mut history = EconomyHistory::default();
+        history.record(&economy(start, 1.));
+        history.record(&economy(start + 60, 2.));
+        history.record(&economy(start + DAY_SECS, 3.));
+        assert_eq!(
+            history.series(InstrumentId::Gold),
+            [(start + 60, 2.), (start + DAY_SECS, 3.)]
+        );
+
+        history.record(&economy(start + MAX_AGE_SECS + 120, 4.));
+        assert_eq!(history.series(InstrumentId::Gold).len(), 2);
+        assert!(history.series(InstrumentId::Wti).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::xlsx;
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Xlsx];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+// the raw numbers stay numbers in JSON and XLSX, the formatted values are text
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    // the sheet of XLSX, the key of JSON and the file name suffix of CSV
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+fn csv_field(cell: &Cell) -> String {
+    let text = match cell {
+        Cell::Text(text) => text.clone(),
+        Cell::Number(v) if v.is_finite() => v.to_string(),
+        Cell::Number(_) => String::default(),
+    };
+
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn csv(table: &Table) -> String {
+    let header = table.header.iter().map(|v| Cell::Text(v.clone()));
+    let mut lines = vec![header.map(|v| csv_field(&v)).collect::<Vec<_>>().join(",")];
+    for row in table.rows.iter() {
+        lines.push(row.iter().map(csv_field).collect::<Vec<_>>().join(","));
+    }
+    lines.join("\r\n") + "\r\n"
+}
+
+// every row is an object keyed by the header
+fn json(tables: &[Table]) -> Value {
+    let mut root = Map::new();
+    for table in tables {
+        let rows = table
+            .rows
+            .iter()
+            .map(|row| {
+                let object = table
+                    .header
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(key, cell)| {
+                        let value = match cell {
+                            Cell::Text(text) => Value::from(text.clone()),
+                            Cell::Number(v) => Value::from(*v),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect::<Map<_, _>>();
+                Value::Object(object)
+            })
+            .collect();
+        root.insert(table.name.clone(), Value::Array(rows));
+    }
+    Value::Object(root)
+}
+
+// `{dir}/{stem}.{ext}`, or `{dir}/{stem}-{n}.{ext}` when an earlier export has the name
+pub fn unused_path(dir: &Path, stem: &str, format: ExportFormat) -> PathBuf {
+    let ext = format.extension();
+    let mut path = dir.join(format!("{stem}.{ext}"));
+    for n in 2.. {
+        if !path.exists() {
+            break;
+        }
+        path = dir.join(format!("{stem}-{n}.{ext}"));
+    }
+    path
+}
+
+// Writes `path`. CSV has no sheets, so every table after the first one goes to
+// `{stem}-{name}.csv` next to it, numbered like `unused_path` rather than replacing the
+// file of an earlier export. Returns the written files.
+pub fn write(path: &Path, format: ExportFormat, tables: &[Table]) -> Result<Vec<PathBuf>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let path = path.to_path_buf();
+
+    match format {
+        ExportFormat::Csv => {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let mut paths = vec![];
+            for (i, table) in tables.iter().enumerate() {
+                let path = if i == 0 {
+                    path.clone()
+                } else {
+                    unused_path(dir, &format!("{stem}-{}", table.name), format)
+                };
+                fs::write(&path, csv(table))?;
+                paths.push(path);
+            }
+            Ok(paths)
+        }
+        ExportFormat::Json => {
+            fs::write(&path, serde_json::to_string_pretty(&json(tables))?)?;
+            Ok(vec![path])
+        }
+        ExportFormat::Xlsx => {
+            let mut writer = BufWriter::new(File::create(&path)?);
+            xlsx::write(tables, &mut writer)?;
+            // the flush on drop would swallow an error, e.g. of a full disk
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            Ok(vec![path])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::io::Read;
+
+    fn tables() -> Vec<Table> {
+        vec![
+            Table {
+                name: "latest".to_string(),
+                header: vec!["symbol".to_string(), "price".to_string()],
+                rows: vec![
+                    vec![Cell::Text("BTC".to_string()), Cell::Number(61927.5)],
+                    vec![Cell::Text("A,\"B\"".to_string()), Cell::Number(f64::NAN)],
+                ],
+            },
+            Table {
+                name: "history".to_string(),
+                header: vec!["timestamp".to_string()],
+                rows: vec![vec![Cell::Number(1.)]],
+            },
+        ]
+    }
+
+    #[test]
+    fn write_csv_per_table() {
+        let dir = test_support::temp_dir();
+        let paths = write(&dir.join("picon.csv"), ExportFormat::Csv, &tables()).unwrap();
+
+        assert_eq!(
+            paths,
+            [dir.join("picon.csv"), dir.join("picon-history.csv")]
+        );
+        assert_eq!(
+            fs::read_to_string(&paths[0]).unwrap(),
+            "symbol,price\r\nBTC,61927.5\r\n\"A,\"\"B\"\"\",\r\n"
+        );
+    }
+
+    #[test]
+    fn write_csv_keeps_earlier_tables() {
+        let dir = test_support::temp_dir();
+        fs::write(dir.join("picon-history.csv"), "earlier").unwrap();
+
+        let paths = write(&dir.join("picon.csv"), ExportFormat::Csv, &tables()).unwrap();
+        assert_eq!(paths[1], dir.join("picon-history-2.csv"));
+        assert_eq!(
+            fs::read_to_string(dir.join("picon-history.csv")).unwrap(),
+            "earlier"
+        );
+    }
+
+    #[test]
+    fn write_json_keyed_by_table() {
+        let dir = test_support::temp_dir();
+        let paths = write(&dir.join("picon.json"), ExportFormat::Json, &tables()).unwrap();
+
+        let value = serde_json::from_str::<Value>(&fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        assert_eq!(value["latest"][0]["symbol"], "BTC");
+        assert_eq!(value["latest"][0]["price"], 61927.5);
+        assert_eq!(value["history"][0]["timestamp"], 1.);
+    }
+
+    #[test]
+    fn write_xlsx() {
+        let dir = test_support::temp_dir();
+        let paths = write(&dir.join("picon.xlsx"), ExportFormat::Xlsx, &tables()).unwrap();
+        assert_eq!(paths, [dir.join("picon.xlsx")]);
+
+        let mut archive = zip::ZipArchive::new(File::open(&paths[0]).unwrap()).unwrap();
+        let mut workbook = String::new();
+        archive
+            .by_name("xl/workbook.xml")
+            .unwrap()
+            .read_to_string(&mut workbook)
+            .unwrap();
+        assert!(workbook.contains(r#"<sheet name="history" sheetId="2" r:id="rId2"/>"#));
+        assert!(archive.by_name("xl/worksheets/sheet2.xml").is_ok());
+    }
+
+    #[test]
+    fn unused_path_keeps_earlier_exports() {
+        let dir = test_support::temp_dir();
+        assert_eq!(
+            unused_path(&dir, "picon", ExportFormat::Json),
+            dir.join("picon.json")
+        );
+
+        fs::write(dir.join("picon.json"), "{}").unwrap();
+        fs::write(dir.join("picon-2.json"), "{}").unwrap();
+        assert_eq!(
+            unused_path(&dir, "picon", ExportFormat::Json),
+            dir.join("picon-3.json")
+        );
+    }
+}
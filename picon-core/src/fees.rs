@@ -0,0 +1,375 @@
+use crate::config::FeeEndpoint;
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+use tokio::task::JoinSet;
+
+// the base fee of a Solana transaction with one signature
+pub const SOLANA_BASE_LAMPORTS: f64 = 5000.;
+
+// the blocks sampled by `eth_feeHistory`, the priority fee is their median tip
+const FEE_HISTORY_BLOCKS: u32 = 4;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    #[default]
+    Ethereum,
+    Arbitrum,
+    Optimism,
+    Base,
+    Bsc,
+    Polygon,
+    Solana,
+}
+
+impl Chain {
+    pub const ALL: [Chain; 7] = [
+        Chain::Ethereum,
+        Chain::Arbitrum,
+        Chain::Optimism,
+        Chain::Base,
+        Chain::Bsc,
+        Chain::Polygon,
+        Chain::Solana,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Chain::Ethereum => "Ethereum",
+            Chain::Arbitrum => "Arbitrum",
+            Chain::Optimism => "Optimism",
+            Chain::Base => "Base",
+            Chain::Bsc => "BSC",
+            Chain::Polygon => "Polygon",
+            Chain::Solana => "Solana",
+        }
+    }
+
+    // the symbol of the coin paying the fees, as listed by CMC
+    pub fn native_symbol(self) -> &'static str {
+        match self {
+            Chain::Ethereum | Chain::Arbitrum | Chain::Optimism | Chain::Base => "ETH",
+            Chain::Bsc => "BNB",
+            Chain::Polygon => "POL",
+            Chain::Solana => "SOL",
+        }
+    }
+
+    pub fn is_evm(self) -> bool {
+        self != Chain::Solana
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Transfer,
+    Swap,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 2] = [Operation::Transfer, Operation::Swap];
+
+    // the typical gas of the EVM chains or compute units of Solana
+    fn units(self, chain: Chain) -> f64 {
+        match (self, chain.is_evm()) {
+            (Operation::Transfer, true) => 21_000.,
+            (Operation::Swap, true) => 150_000.,
+            (Operation::Transfer, false) => 450.,
+            (Operation::Swap, false) => 200_000.,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ChainFee {
+    pub chain: Chain,
+
+    // gwei for the EVM chains, lamports per signature for Solana
+    pub base_fee: f64,
+
+    // gwei for the EVM chains, micro-lamports per compute unit for Solana
+    pub priority_fee: f64,
+}
+
+impl ChainFee {
+    // in the native coin, without the L1 data fee of the rollups
+    pub fn cost(&self, op: Operation) -> f64 {
+        let units = op.units(self.chain);
+        if self.chain.is_evm() {
+            units * (self.base_fee + self.priority_fee) / 1e9
+        } else {
+            (self.base_fee + self.priority_fee * units / 1e6) / 1e9
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Fees {
+    #[serde(skip)]
+    pub errors: Vec<String>,
+
+    // at least one endpoint could not be reached
+    #[serde(skip)]
+    pub is_offline: bool,
+
+    // in the order of the endpoints
+    pub items: Vec<ChainFee>,
+
+    pub timestamp: i64,
+}
+
+impl Fees {
+    // the chains of `other` replace the same chains, the failed ones keep their last fee
+    pub fn merge(&mut self, other: Fees) {
+        for item in other.items {
+            match self.items.iter_mut().find(|v| v.chain == item.chain) {
+                Some(v) => *v = item,
+                None => self.items.push(item),
+            }
+        }
+        self.timestamp = other.timestamp;
+    }
+
+    pub fn get(&self, chain: Chain) -> Option<&ChainFee> {
+        self.items.iter().find(|v| v.chain == chain)
+    }
+}
+
+// Every endpoint is queried at once. The chains which failed are reported in
+// `Fees::errors`, the others are still returned.
+pub async fn fetch(
+    client: &reqwest::Client,
+    endpoints: &[FeeEndpoint],
+    save_path: &Path,
+) -> Result<Fees> {
+    // the set aborts the requests still running when this fetch is cancelled
+    let mut tasks = JoinSet::new();
+    for (i, endpoint) in endpoints.iter().cloned().enumerate() {
+        let client = client.clone();
+        tasks.spawn(async move {
+            let fee = fetch_chain(&client, endpoint.chain, &endpoint.url).await;
+            (i, endpoint.chain, fee)
+        });
+    }
+
+    let mut results = Vec::with_capacity(endpoints.len());
+    while let Some(result) = tasks.join_next().await {
+        results.push(result?);
+    }
+    results.sort_by_key(|v| v.0);
+
+    let mut fees = Fees {
+        timestamp: util::timestamp(),
+        ..Default::default()
+    };
+
+    for result in results {
+        match result {
+            (_, _, Ok(fee)) => fees.items.push(fee),
+            (_, chain, Err(e)) => {
+                fees.is_offline |= util::is_network_error(&e);
+                fees.errors
+                    .push(format!("fetch {} fee error: {e:?}", chain.name()));
+            }
+        }
+    }
+
+    if fees.errors.is_empty() {
+        _ = cache::save(save_path, &fees);
+    }
+
+    Ok(fees)
+}
+
+pub async fn fetch_chain(client: &reqwest::Client, chain: Chain, url: &str) -> Result<ChainFee> {
+    if !chain.is_evm() {
+        let fees = rpc(client, url, "getRecentPrioritizationFees", json!([])).await?;
+        let fees = fees
+            .as_array()
+            .ok_or_else(|| anyhow!("invalid prioritization fees: {fees}"))?
+            .iter()
+            .filter_map(|v| v["prioritizationFee"].as_f64())
+            .collect::<Vec<_>>();
+
+        return Ok(ChainFee {
+            chain,
+            base_fee: SOLANA_BASE_LAMPORTS,
+            priority_fee: median(fees),
+        });
+    }
+
+    let params = json!([format!("{FEE_HISTORY_BLOCKS:#x}"), "latest", [50]]);
+    match rpc(client, url, "eth_feeHistory", params).await {
+        Ok(history) => {
+            // the last base fee is the one of the pending block
+            let base_fee = history["baseFeePerGas"]
+                .as_array()
+                .and_then(|v| v.last())
+                .and_then(parse_hex)
+                .ok_or_else(|| anyhow!("invalid fee history: {history}"))?;
+
+            let tips = history["reward"]
+                .as_array()
+                .map(|v| v.iter().filter_map(|r| parse_hex(&r[0])).collect())
+                .unwrap_or_default();
+
+            Ok(ChainFee {
+                chain,
+                base_fee: base_fee / 1e9,
+                priority_fee: median(tips) / 1e9,
+            })
+        }
+
+        // the chains without EIP-1559 only have a gas price
+        Err(e) if !util::is_network_error(&e) => {
+            let price = rpc(client, url, "eth_gasPrice", json!([])).await?;
+            let price = parse_hex(&price).ok_or_else(|| anyhow!("invalid gas price: {price}"))?;
+
+            Ok(ChainFee {
+                chain,
+                base_fee: price / 1e9,
+                priority_fee: 0.,
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// the `result` of a JSON-RPC 2.0 call
+async fn rpc(client: &reqwest::Client, url: &str, method: &str, params: Value) -> Result<Value> {
+    let resp = client
+        .post(url)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Value>()
+        .await?;
+
+    if !resp["error"].is_null() {
+        return Err(anyhow!("{method}: {}", resp["error"]));
+    }
+
+    Ok(resp["result"].clone())
+}
+
+fn parse_hex(v: &Value) -> Option<f64> {
+    let text = v.as_str()?.trim_start_matches("0x");
+    u128::from_str_radix(text, 16).ok().map(|v| v as f64)
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.;
+    }
+
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    fn endpoint(server: &MockServer, chain: Chain, path: &str) -> FeeEndpoint {
+        FeeEndpoint {
+            chain,
+            url: format!("{}{path}", server.cmc_url()),
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_recorded_fees() {
+        let server = MockServer::start();
+        server.route_rpc("/eth", "eth_feeHistory", test_support::RPC_ETH_FEE_HISTORY);
+        server.route("/bsc", 200, test_support::RPC_METHOD_NOT_FOUND);
+        server.route_rpc("/bsc", "eth_gasPrice", test_support::RPC_ETH_GAS_PRICE);
+        server.route("/sol", 200, test_support::RPC_SOL_PRIORITY_FEES);
+
+        let dir = test_support::temp_dir();
+
+        let save_path = dir.join("fees.json");
+        let endpoints = [
+            endpoint(&server, Chain::Ethereum, "/eth"),
+            endpoint(&server, Chain::Bsc, "/bsc"),
+            endpoint(&server, Chain::Solana, "/sol"),
+        ];
+
+        let fees = fetch(&reqwest::Client::new(), &endpoints, &save_path)
+            .await
+            .unwrap();
+        assert!(fees.errors.is_empty(), "{:?}", fees.errors);
+        assert!(save_path.exists());
+
+        let eth = &fees.items[0];
+        assert_eq!((eth.base_fee, eth.priority_fee), (21., 1.));
+        assert_eq!(eth.cost(Operation::Transfer), 21_000. * 22. / 1e9);
+
+        let bsc = &fees.items[1];
+        assert_eq!(
+            (bsc.chain, bsc.base_fee, bsc.priority_fee),
+            (Chain::Bsc, 3., 0.)
+        );
+
+        let sol = &fees.items[2];
+        assert_eq!(
+            (sol.base_fee, sol.priority_fee),
+            (SOLANA_BASE_LAMPORTS, 1000.)
+        );
+        assert_eq!(sol.cost(Operation::Swap), (5000. + 200.) / 1e9);
+
+        let body = &server.requests()[0].body;
+        assert!(body.contains("\"method\":\"eth_feeHistory\""), "{body}");
+    }
+
+    #[tokio::test]
+    async fn fetch_partial_failure() {
+        let server = MockServer::start();
+        server.route_rpc("/eth", "eth_feeHistory", test_support::RPC_ETH_FEE_HISTORY);
+
+        let dir = test_support::temp_dir();
+
+        let save_path = dir.join("fees.json");
+        let endpoints = [
+            endpoint(&server, Chain::Ethereum, "/eth"),
+            FeeEndpoint {
+                chain: Chain::Base,
+                url: test_support::unreachable_url(),
+            },
+        ];
+
+        let fees = fetch(&reqwest::Client::new(), &endpoints, &save_path)
+            .await
+            .unwrap();
+        assert_eq!(fees.items.len(), 1);
+        assert_eq!(fees.errors.len(), 1);
+        assert!(fees.is_offline);
+        assert!(!save_path.exists());
+
+        let mut last = Fees {
+            items: vec![ChainFee {
+                chain: Chain::Base,
+                base_fee: 0.01,
+                priority_fee: 0.,
+            }],
+            ..Default::default()
+        };
+        last.merge(fees);
+        assert_eq!(last.items.len(), 2);
+        assert_eq!(last.get(Chain::Base).unwrap().base_fee, 0.01);
+        assert_eq!(last.get(Chain::Ethereum).unwrap().base_fee, 21.);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::config::Locale;
+use chrono::{Local, TimeZone};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub group_separator: char,
+    pub decimal_separator: char,
+    pub group_size: usize,
+}
+
+pub fn number_format(locale: Locale) -> NumberFormat {
+    match locale {
+        Locale::En | Locale::ZhCN | Locale::ZhTW | Locale::Ja | Locale::Ko => NumberFormat {
+            group_separator: ',',
+            decimal_separator: '.',
+            group_size: 3,
+        },
+    }
+}
+
+// the units of the compact notation, from the largest to the smallest
+fn compact_units(locale: Locale) -> &'static [(f64, &'static str)] {
+    match locale {
+        Locale::En => &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")],
+        Locale::ZhCN => &[(1e12, "万亿"), (1e8, "亿"), (1e4, "万")],
+        Locale::ZhTW => &[(1e12, "兆"), (1e8, "億"), (1e4, "萬")],
+        Locale::Ja => &[(1e12, "兆"), (1e8, "億"), (1e4, "万")],
+        Locale::Ko => &[(1e12, "조"), (1e8, "억"), (1e4, "만")],
+    }
+}
+
+pub fn currency_symbol(currency: &str) -> Option<&'static str> {
+    match currency.to_uppercase().as_str() {
+        "USD" => Some("$"),
+        "CNY" | "JPY" => Some("¥"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "KRW" => Some("₩"),
+        "BTC" => Some("₿"),
+        "ETH" => Some("Ξ"),
+        _ => None,
+    }
+}
+
+// grouped digits with a fixed number of decimals, e.g. "1,234,567.89"
+pub fn number(locale: Locale, value: f64, decimals: usize) -> String {
+    if !value.is_finite() {
+        return "-".to_string();
+    }
+
+    let nf = number_format(locale);
+    let text = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut grouped = String::new();
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % nf.group_size == 0 {
+            grouped.push(nf.group_separator);
+        }
+        grouped.push(ch);
+    }
+
+    if !frac_part.is_empty() {
+        grouped.push(nf.decimal_separator);
+        grouped.push_str(frac_part);
+    }
+
+    let is_zero = text.chars().all(|c| c == '0' || c == '.');
+    if value < 0. && !is_zero {
+        format!("-{grouped}")
+    } else {
+        grouped
+    }
+}
+
+// 1.23T, 3.40B in English, 1.23万亿, 3.40亿 in Chinese
+pub fn compact(locale: Locale, value: f64) -> String {
+    let units = compact_units(locale);
+    for (i, &(base, unit)) in units.iter().enumerate() {
+        // picked by the value rounded in the next smaller unit, 999,999 is "1.00M" rather than "1,000.00K"
+        let lower = units.get(i + 1).map_or(1., |&(base, _)| base);
+        if (value.abs() / lower * 100.).round() >= base / lower * 100. {
+            return format!("{}{unit}", number(locale, value / base, 2));
+        }
+    }
+
+    number(locale, value, 2)
+}
+
+// the precision follows the magnitude of the price
+pub fn price(locale: Locale, value: f64) -> String {
+    let decimals = match value.abs() {
+        p if p < 0.000_01 => 6,
+        p if p < 0.000_1 => 5,
+        p if p < 0.001 => 4,
+        p if p < 0.01 => 3,
+        p if p > 10_000. => 0,
+        _ => 2,
+    };
+
+    number(locale, value, decimals)
+}
+
+pub fn percent(locale: Locale, value: f64) -> String {
+    let decimals = if value.abs() >= 100. { 0 } else { 2 };
+    format!("{}%", number(locale, value, decimals))
+}
+
+// "$1.23T" or "1.23T DOGE" when the currency has no symbol
+pub fn money(locale: Locale, currency: &str, value: f64, is_compact: bool) -> String {
+    let text = if is_compact {
+        compact(locale, value)
+    } else {
+        price(locale, value)
+    };
+
+    match currency_symbol(currency) {
+        Some(symbol) if value < 0. => format!("-{symbol}{}", text.trim_start_matches('-')),
+        Some(symbol) => format!("{symbol}{text}"),
+        None => format!("{text} {currency}"),
+    }
+}
+
+// shown in the local timezone
+pub fn datetime(locale: Locale, utc_seconds: i64) -> String {
+    let Some(time) = Local.timestamp_opt(utc_seconds, 0).single() else {
+        return "-".to_string();
+    };
+
+    let pattern = match locale {
+        Locale::En => "%Y-%m-%d %H:%M",
+        Locale::ZhCN | Locale::ZhTW | Locale::Ja => "%Y/%m/%d %H:%M",
+        Locale::Ko => "%Y. %m. %d. %H:%M",
+    };
+
+    time.format(pattern).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_digits() {
+        assert_eq!(number(Locale::En, 1234567890123., 0), "1,234,567,890,123");
+        assert_eq!(number(Locale::En, 123., 0), "123");
+        assert_eq!(number(Locale::En, -1234.5, 2), "-1,234.50");
+        assert_eq!(number(Locale::En, -0.001, 2), "0.00");
+        assert_eq!(number(Locale::En, f64::NAN, 2), "-");
+    }
+
+    #[test]
+    fn compact_notation() {
+        assert_eq!(compact(Locale::En, 1_234_567_890_123.), "1.23T");
+        assert_eq!(compact(Locale::En, 3_400_000_000.), "3.40B");
+        assert_eq!(compact(Locale::En, 999.), "999.00");
+        assert_eq!(compact(Locale::En, 999_999.), "1.00M");
+        assert_eq!(compact(Locale::En, -999.999), "-1.00K");
+        assert_eq!(compact(Locale::ZhCN, 99_999_999.), "1.00亿");
+        assert_eq!(compact(Locale::ZhCN, 1_234_567_890_123.), "1.23万亿");
+        assert_eq!(compact(Locale::ZhCN, 340_000_000.), "3.40亿");
+        assert_eq!(compact(Locale::ZhCN, 56_000.), "5.60万");
+        assert_eq!(compact(Locale::Ja, 340_000_000.), "3.40億");
+        assert_eq!(compact(Locale::Ko, 56_000.), "5.60만");
+    }
+
+    #[test]
+    fn prices_and_percents() {
+        assert_eq!(price(Locale::En, 61927.34), "61,927");
+        assert_eq!(price(Locale::En, 1.17354), "1.17");
+        assert_eq!(price(Locale::En, 0.0000273812), "0.00003");
+        assert_eq!(percent(Locale::En, 21.37658), "21.38%");
+        assert_eq!(percent(Locale::En, -188.7), "-189%");
+    }
+
+    #[test]
+    fn money_symbols() {
+        assert_eq!(money(Locale::En, "USD", 2_321_984_305_861., true), "$2.32T");
+        assert_eq!(money(Locale::ZhCN, "CNY", 340_000_000., true), "¥3.40亿");
+        assert_eq!(money(Locale::En, "usd", -1.5, false), "-$1.50");
+        assert_eq!(money(Locale::En, "DOGE", 12., false), "12.00 DOGE");
+    }
+}
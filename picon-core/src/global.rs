@@ -0,0 +1,269 @@
+use crate::history::{DAY_SECS, HOUR_SECS};
+use crate::latest::{self, Latest};
+use crate::{cache, util};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+// the snapshots older than this are dropped
+pub const MAX_AGE_SECS: i64 = 365 * DAY_SECS;
+
+// the coins compared with BTC by the altcoin season index
+const ALTCOIN_SEASON_COINS: usize = 100;
+
+// Market-wide metrics of CMC, in USD.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GlobalMetrics {
+    pub total_market_cap: f64,
+    pub total_volume_24h: f64,
+    pub btc_dominance: f64,
+    pub eth_dominance: f64,
+    pub stablecoin_market_cap: f64,
+    pub defi_market_cap: f64,
+    pub altcoin_market_cap: f64,
+
+    // upstream data time
+    pub last_updated: i64,
+}
+
+impl GlobalMetrics {
+    // the share of the total market cap in percent
+    pub fn share(&self, market_cap: f64) -> f64 {
+        if self.total_market_cap > 0. {
+            market_cap / self.total_market_cap * 100.
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+// The percentage of the top coins, without BTC and the stablecoins, which did better than
+// BTC over 90 days. Above 75 is commonly called an altcoin season.
+pub fn altcoin_season(latest: &Latest) -> Option<f64> {
+    let btc = latest.data.iter().find(|v| v.symbol == "BTC")?;
+    let btc_change = btc.quote.value.percent_change_90d;
+
+    let mut coins = latest
+        .data
+        .iter()
+        .filter(|v| v.id != btc.id && !v.tags.iter().any(|t| t == "stablecoin"))
+        .collect::<Vec<_>>();
+    coins.sort_by_key(|v| v.rank);
+    coins.truncate(ALTCOIN_SEASON_COINS);
+
+    if coins.is_empty() || !btc_change.is_finite() {
+        return None;
+    }
+
+    let count = coins
+        .iter()
+        .filter(|v| v.quote.value.percent_change_90d > btc_change)
+        .count();
+    Some(count as f64 / coins.len() as f64 * 100.)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GlobalPoint {
+    pub timestamp: i64,
+    pub btc_dominance: f64,
+    pub eth_dominance: f64,
+
+    // the shares of the total market cap in percent
+    pub stablecoin_share: f64,
+    pub defi_share: f64,
+
+    // none when the listings had no BTC
+    #[serde(default)]
+    pub altcoin_season: Option<f64>,
+}
+
+// Hourly snapshots of the global metrics, to chart the dominance shifts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GlobalHistory {
+    // in time order
+    #[serde(default)]
+    pub points: Vec<GlobalPoint>,
+}
+
+impl GlobalHistory {
+    pub fn record(&mut self, metrics: &GlobalMetrics, altcoin_season: Option<f64>) {
+        let now = metrics.last_updated;
+        if now <= 0 {
+            return;
+        }
+
+        let point = GlobalPoint {
+            timestamp: now,
+            btc_dominance: metrics.btc_dominance,
+            eth_dominance: metrics.eth_dominance,
+            stablecoin_share: metrics.share(metrics.stablecoin_market_cap),
+            defi_share: metrics.share(metrics.defi_market_cap),
+            altcoin_season,
+        };
+
+        match self.points.last_mut() {
+            Some(last) if now < last.timestamp => return,
+            Some(last) if now / HOUR_SECS == last.timestamp / HOUR_SECS => *last = point,
+            _ => self.points.push(point),
+        }
+
+        self.points.retain(|p| now - p.timestamp <= MAX_AGE_SECS);
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Resp {
+    status: latest::LatestStatus,
+
+    #[serde(default)]
+    data: Option<RespData>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RespData {
+    btc_dominance: f64,
+    eth_dominance: f64,
+    last_updated: String,
+    quote: HashMap<String, RespQuote>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RespQuote {
+    total_market_cap: f64,
+    total_volume_24h: f64,
+
+    #[serde(default)]
+    stablecoin_market_cap: f64,
+
+    #[serde(default)]
+    defi_market_cap: f64,
+
+    #[serde(default)]
+    altcoin_market_cap: f64,
+}
+
+// curl -H "X-CMC_PRO_API_KEY: $API_KEY" -H "Accept: application/json" -G https://pro-api.coinmarketcap.com/v1/global-metrics/quotes/latest
+pub async fn fetch(
+    client: &reqwest::Client,
+    api_base: &str,
+    api_key: &str,
+    save_path: &Path,
+) -> Result<GlobalMetrics> {
+    let resp = client
+        .get(format!("{api_base}/v1/global-metrics/quotes/latest"))
+        .headers(latest::headers(api_key))
+        .send()
+        .await?
+        .json::<Resp>()
+        .await?;
+
+    if let Some(e) = resp.status.error_message {
+        return Err(anyhow!(e));
+    }
+
+    let data = resp.data.ok_or_else(|| anyhow!("no global metrics"))?;
+    let quote = data
+        .quote
+        .get("USD")
+        .ok_or_else(|| anyhow!("no USD quote of the global metrics"))?;
+
+    let metrics = GlobalMetrics {
+        total_market_cap: quote.total_market_cap,
+        total_volume_24h: quote.total_volume_24h,
+        btc_dominance: data.btc_dominance,
+        eth_dominance: data.eth_dominance,
+        stablecoin_market_cap: quote.stablecoin_market_cap,
+        defi_market_cap: quote.defi_market_cap,
+        altcoin_market_cap: quote.altcoin_market_cap,
+        last_updated: util::utc_seconds_from_str(&data.last_updated).unwrap_or_default(),
+    };
+
+    _ = cache::save(save_path, &metrics);
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    #[tokio::test]
+    async fn fetch_recorded_metrics() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("global.json");
+
+        let metrics = fetch(
+            &reqwest::Client::new(),
+            &server.cmc_url(),
+            "test-key",
+            &save_path,
+        )
+        .await
+        .unwrap();
+        assert_eq!(metrics.btc_dominance, 52.2289);
+        assert_eq!(metrics.eth_dominance, 17.6244);
+        assert!(metrics.stablecoin_market_cap > 0.);
+        assert!(metrics.defi_market_cap > 0.);
+        assert!(metrics.last_updated > 0);
+        assert!(save_path.exists());
+
+        let req = &server.requests()[0];
+        assert_eq!(req.path, test_support::GLOBAL_METRICS_PATH);
+        assert_eq!(req.headers["x-cmc_pro_api_key"], "test-key");
+    }
+
+    #[tokio::test]
+    async fn fetch_invalid_key() {
+        let server = MockServer::start();
+        server.route(
+            test_support::GLOBAL_METRICS_PATH,
+            401,
+            test_support::CMC_LISTINGS_ERROR,
+        );
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("global.json");
+
+        let e = fetch(
+            &reqwest::Client::new(),
+            &server.cmc_url(),
+            "test-key",
+            &save_path,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(e.to_string(), "This API Key is invalid.");
+        assert!(!save_path.exists());
+    }
+
+    #[test]
+    fn altcoin_season_of_listings() {
+        let latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+
+        // ETH, BNB and SOL did better than BTC over 90 days, USDT is not counted
+        assert_eq!(altcoin_season(&latest), Some(100.));
+        assert_eq!(altcoin_season(&Latest::default()), None);
+    }
+
+    #[test]
+    fn record_keeps_one_point_per_hour() {
+        let start = 1_700_000_000 / HOUR_SECS * HOUR_SECS;
+        let metrics = |last_updated, btc_dominance| GlobalMetrics {
+            total_market_cap: 100.,
+            stablecoin_market_cap: 5.,
+            btc_dominance,
+            last_updated,
+            ..Default::default()
+        };
+
+        let mut history = GlobalHistory::default();
+        history.record(&metrics(start, 50.), Some(25.));
+        history.record(&metrics(start + 60, 51.), None);
+        history.record(&metrics(start + HOUR_SECS, 52.), None);
+
+        assert_eq!(history.points.len(), 2);
+        assert_eq!(history.points[0].btc_dominance, 51.);
+        assert_eq!(history.points[0].stablecoin_share, 5.);
+        assert_eq!(history.points[0].altcoin_season, None);
+    }
+}
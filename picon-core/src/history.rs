@@ -0,0 +1,164 @@
+use crate::latest::{Latest, LatestDataItem};
+use crate::util;
+use std::collections::HashMap;
+
+pub const HOUR_SECS: i64 = 3600;
+pub const DAY_SECS: i64 = 24 * HOUR_SECS;
+
+// the snapshots older than this are dropped
+pub const MAX_AGE_SECS: i64 = 30 * DAY_SECS;
+
+// Local price snapshots of the listed coins in one currency, recorded after every
+// listings fetch and kept at most one per hour.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct History {
+    pub currency: String,
+
+    // coin id -> (utc seconds, price), in time order
+    #[serde(default)]
+    pub coins: HashMap<u64, Vec<(i64, f64)>>,
+
+    // coin id -> the highest price seen on this device, never dropped
+    #[serde(default)]
+    pub local_high: HashMap<u64, f64>,
+}
+
+impl History {
+    pub fn new(currency: &str) -> Self {
+        Self {
+            currency: currency.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // one file per currency under `Config::cache_dir`
+    pub fn file_name(currency: &str) -> String {
+        format!("history-{}.json", currency.to_lowercase())
+    }
+
+    pub fn record(&mut self, latest: &Latest) {
+        let now = latest.addition_info.timestamp;
+        if now <= 0 {
+            return;
+        }
+
+        for item in latest.data.iter() {
+            let price = item.quote.value.price;
+            if !price.is_finite() || price <= 0. {
+                continue;
+            }
+
+            let high = self.local_high.entry(item.id).or_default();
+            *high = high.max(price);
+
+            let ts = util::utc_seconds_from_str(&item.last_updated).unwrap_or(now);
+            let points = self.coins.entry(item.id).or_default();
+            match points.last_mut() {
+                Some(last) if ts < last.0 => continue,
+                Some(last) if ts / HOUR_SECS == last.0 / HOUR_SECS => *last = (ts, price),
+                _ => points.push((ts, price)),
+            }
+        }
+
+        for points in self.coins.values_mut() {
+            points.retain(|p| now - p.0 <= MAX_AGE_SECS);
+        }
+        self.coins.retain(|_, v| !v.is_empty());
+    }
+
+    pub fn series(&self, coin_id: u64, since: i64) -> Vec<(i64, f64)> {
+        self.coins
+            .get(&coin_id)
+            .map(|v| v.iter().filter(|p| p.0 >= since).copied().collect())
+            .unwrap_or_default()
+    }
+
+    // The local snapshots of the last 7 days, plus the prices 7 days and 24 hours ago implied
+    // by the provider's percent changes, so a coin seen for the first time still has a shape.
+    pub fn trend_7d(&self, item: &LatestDataItem, now: i64) -> Vec<(i64, f64)> {
+        let value = &item.quote.value;
+        let mut points = self.series(item.id, now - 7 * DAY_SECS);
+
+        let anchors = [
+            (now - 7 * DAY_SECS, value.percent_change_7d),
+            (now - DAY_SECS, value.percent_change_24h),
+        ];
+
+        for (ts, percent) in anchors {
+            let price = value.price / (1. + percent / 100.);
+            let is_covered = points.iter().any(|p| (p.0 - ts).abs() < HOUR_SECS);
+            if price.is_finite() && price > 0. && !is_covered {
+                points.push((ts, price));
+            }
+        }
+
+        points.retain(|p| p.0 <= now);
+        if !points.iter().any(|p| p.0 == now) && value.price.is_finite() {
+            points.push((now, value.price));
+        }
+
+        points.sort_by_key(|p| p.0);
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::latest::{LatestDataItemQuote, LatestDataItemQuoteValue};
+
+    fn latest(timestamp: i64, price: f64) -> Latest {
+        let mut latest = Latest::default();
+        latest.addition_info.timestamp = timestamp;
+        latest.data.push(LatestDataItem {
+            id: 1,
+            symbol: "BTC".to_string(),
+            quote: LatestDataItemQuote {
+                currency: "USD".to_string(),
+                value: LatestDataItemQuoteValue {
+                    price,
+                    percent_change_24h: 25.,
+                    percent_change_7d: -50.,
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        });
+        latest
+    }
+
+    #[test]
+    fn record_keeps_one_snapshot_per_hour() {
+        let mut history = History::new("USD");
+        let start = 1_700_000_000 / HOUR_SECS * HOUR_SECS;
+
+        history.record(&latest(start, 1.));
+        history.record(&latest(start + 60, 2.));
+        history.record(&latest(start + HOUR_SECS, 3.));
+        assert_eq!(
+            history.coins[&1],
+            vec![(start + 60, 2.), (start + HOUR_SECS, 3.)]
+        );
+
+        history.record(&latest(start + MAX_AGE_SECS + 120, 1.));
+        assert_eq!(history.coins[&1].len(), 2);
+        assert_eq!(history.local_high[&1], 3.);
+    }
+
+    #[test]
+    fn trend_falls_back_to_percent_changes() {
+        let history = History::new("USD");
+        let now = 1_700_000_000;
+        let item = latest(now, 125.).data.remove(0);
+
+        let points = history.trend_7d(&item, now);
+        assert_eq!(
+            points,
+            vec![
+                (now - 7 * DAY_SECS, 250.),
+                (now - DAY_SECS, 100.),
+                (now, 125.)
+            ]
+        );
+    }
+}
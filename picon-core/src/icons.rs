@@ -0,0 +1,140 @@
+use crate::cache;
+use anyhow::{anyhow, Result};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// the logos kept under `Config::icons_dir`, the least recently used are removed first
+pub const MAX_ICONS: usize = 1000;
+
+pub fn url(api_base: &str, coin_id: u64) -> String {
+    format!("{api_base}/{coin_id}.png")
+}
+
+pub fn path(dir: &Path, coin_id: u64) -> PathBuf {
+    dir.join(format!("{coin_id}.png"))
+}
+
+// the modified time of a file records its last use
+pub fn load(dir: &Path, coin_id: u64) -> Option<Vec<u8>> {
+    let path = path(dir, coin_id);
+    let bytes = fs::read(&path).ok()?;
+
+    if let Err(e) = File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|f| f.set_modified(SystemTime::now()))
+    {
+        log::debug!("{e:?}");
+    }
+
+    Some(bytes)
+}
+
+pub fn evict(dir: &Path, max_icons: usize) -> Result<usize> {
+    let mut files = fs::read_dir(dir)?
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "png"))
+        .map(|e| {
+            let modified = e
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, e.path())
+        })
+        .collect::<Vec<_>>();
+
+    if files.len() <= max_icons {
+        return Ok(0);
+    }
+
+    files.sort();
+    let count = files.len() - max_icons;
+    for (_, path) in files.into_iter().take(count) {
+        fs::remove_file(path)?;
+    }
+
+    Ok(count)
+}
+
+pub async fn fetch(
+    client: &reqwest::Client,
+    api_base: &str,
+    dir: &Path,
+    coin_id: u64,
+) -> Result<Option<Vec<u8>>> {
+    let resp = client.get(url(api_base, coin_id)).send().await?;
+    // the coin has no logo, unlike the other errors this is not worth a retry
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(anyhow!("icon {coin_id}: {}", resp.status()));
+    }
+
+    let bytes = resp.bytes().await?.to_vec();
+
+    fs::create_dir_all(dir)?;
+    cache::write_atomic(&path(dir, coin_id), &bytes)?;
+    // the logo is saved already, a failed cleanup is tried again with the next one
+    if let Err(e) = evict(dir, MAX_ICONS) {
+        log::warn!("{e:?}");
+    }
+
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{temp_dir, MockServer};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn fetch_saves_icon() {
+        let server = MockServer::start();
+        server.route("/icons/1.png", 200, "png");
+        let root = temp_dir();
+        let dir = root.join("icons");
+
+        let base = format!("{}/icons", server.cmc_url());
+        let bytes = fetch(&reqwest::Client::new(), &base, &dir, 1)
+            .await
+            .unwrap();
+
+        assert_eq!(bytes.unwrap(), b"png");
+        assert_eq!(load(&dir, 1).unwrap(), b"png");
+        assert!(fetch(&reqwest::Client::new(), &base, &dir, 2)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(load(&dir, 2).is_none());
+
+        server.route("/icons/3.png", 500, "");
+        assert!(fetch(&reqwest::Client::new(), &base, &dir, 3)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let dir = temp_dir();
+        for coin_id in 1..=3 {
+            fs::write(path(&dir, coin_id), b"png").unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(100 - coin_id);
+            File::options()
+                .write(true)
+                .open(path(&dir, coin_id))
+                .and_then(|f| f.set_modified(modified))
+                .unwrap();
+        }
+
+        // the oldest one is used again
+        load(&dir, 1).unwrap();
+
+        assert_eq!(evict(&dir, 2).unwrap(), 1);
+        assert!(path(&dir, 1).exists());
+        assert!(!path(&dir, 2).exists());
+        assert!(path(&dir, 3).exists());
+    }
+}
@@ -0,0 +1,427 @@
+use crate::{cache, util};
+use anyhow::Result;
+use reqwest::header::{HeaderMap, ACCEPT};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Marker,
+    #[default]
+    Rank,
+    Symbol,
+    Price,
+    H1,
+    H24,
+    D7,
+    D30,
+    MarketCap,
+    Volume24h,
+    CirculatingSupply,
+    LocalHighDistance,
+    Holdings,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct SortSpec {
+    pub key: SortKey,
+    pub is_descending: bool,
+}
+
+impl SortSpec {
+    pub fn asc(key: SortKey) -> Self {
+        Self {
+            key,
+            is_descending: false,
+        }
+    }
+}
+
+// the data outside of the listings which some sort keys need
+pub struct SortContext<'a> {
+    pub marker_symbols: &'a HashSet<String>,
+
+    // coin id -> amount
+    pub holdings: &'a HashMap<u64, f64>,
+
+    // coin id -> the highest price seen on this device
+    pub local_high: &'a HashMap<u64, f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Latest {
+    pub status: LatestStatus,
+
+    #[serde(default)]
+    pub data: Vec<LatestDataItem>,
+
+    #[serde(skip)]
+    pub addition_info: AdditionInfo,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct LatestStatus {
+    pub timestamp: String,
+    pub error_message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct LatestDataItem {
+    pub id: u64,
+    pub symbol: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    // the path of the coin page, e.g. https://coinmarketcap.com/currencies/bitcoin/
+    #[serde(default)]
+    pub slug: String,
+
+    // e.g. "stablecoin"
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    #[serde(rename(deserialize = "cmc_rank"), rename(serialize = "cmc_rank"))]
+    pub rank: u32,
+
+    #[serde(default)]
+    pub last_updated: String,
+
+    #[serde(default)]
+    pub circulating_supply: Option<f64>,
+
+    pub quote: LatestDataItemQuote,
+}
+
+// keyed by the `convert` currency of the request, e.g. {"USD": {...}}
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(
+    from = "HashMap<String, LatestDataItemQuoteValue>",
+    into = "HashMap<String, LatestDataItemQuoteValue>"
+)]
+pub struct LatestDataItemQuote {
+    pub currency: String,
+    pub value: LatestDataItemQuoteValue,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct LatestDataItemQuoteValue {
+    pub price: f64,
+    pub percent_change_24h: f64,
+    pub percent_change_7d: f64,
+
+    #[serde(default)]
+    pub percent_change_1h: f64,
+
+    #[serde(default)]
+    pub percent_change_30d: f64,
+
+    #[serde(default)]
+    pub percent_change_90d: f64,
+
+    #[serde(default)]
+    pub market_cap: f64,
+
+    #[serde(default)]
+    pub volume_24h: f64,
+}
+
+impl From<HashMap<String, LatestDataItemQuoteValue>> for LatestDataItemQuote {
+    fn from(quote: HashMap<String, LatestDataItemQuoteValue>) -> Self {
+        quote
+            .into_iter()
+            .next()
+            .map(|(currency, value)| Self { currency, value })
+            .unwrap_or_default()
+    }
+}
+
+impl From<LatestDataItemQuote> for HashMap<String, LatestDataItemQuoteValue> {
+    fn from(quote: LatestDataItemQuote) -> Self {
+        HashMap::from([(quote.currency, quote.value)])
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AdditionInfo {
+    pub h24_up_count: usize,
+    pub d7_up_count: usize,
+
+    // upstream data time, not the time the data was loaded
+    pub timestamp: i64,
+}
+
+impl Latest {
+    pub fn update_addition_info(&mut self) {
+        self.addition_info.h24_up_count = self
+            .data
+            .iter()
+            .filter(|&v| v.quote.value.percent_change_24h >= 0.)
+            .count();
+
+        self.addition_info.d7_up_count = self
+            .data
+            .iter()
+            .filter(|&v| v.quote.value.percent_change_7d >= 0.)
+            .count();
+
+        self.addition_info.timestamp =
+            util::utc_seconds_from_str(&self.status.timestamp).unwrap_or_default();
+    }
+
+    // Stable, by each spec in turn and then by rank. `SortKey::Marker` ascending puts the
+    // marked symbols first, and unknown values are last in both directions.
+    pub fn sort(&mut self, specs: &[SortSpec], ctx: &SortContext) {
+        self.data.sort_by(|a, b| {
+            specs
+                .iter()
+                .map(|spec| a.compare(b, spec, ctx))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.rank.cmp(&b.rank))
+        });
+    }
+}
+
+impl LatestDataItem {
+    pub fn compare(&self, other: &Self, spec: &SortSpec, ctx: &SortContext) -> Ordering {
+        let ordering = match spec.key {
+            SortKey::Symbol => self.symbol.to_uppercase().cmp(&other.symbol.to_uppercase()),
+            SortKey::Marker => ctx
+                .marker_symbols
+                .contains(&other.symbol)
+                .cmp(&ctx.marker_symbols.contains(&self.symbol)),
+            key => {
+                let (a, b) = (self.sort_value(key, ctx), other.sort_value(key, ctx));
+                match (a.is_nan(), b.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => return Ordering::Greater,
+                    (false, true) => return Ordering::Less,
+                    _ => a.total_cmp(&b),
+                }
+            }
+        };
+
+        if spec.is_descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    // the value of the numeric sort keys, NaN when it is unknown
+    pub fn sort_value(&self, key: SortKey, ctx: &SortContext) -> f64 {
+        let value = &self.quote.value;
+        match key {
+            SortKey::Marker => f64::from(u8::from(ctx.marker_symbols.contains(&self.symbol))),
+            SortKey::Rank => f64::from(self.rank),
+            SortKey::Symbol => f64::NAN,
+            SortKey::Price => value.price,
+            SortKey::H1 => value.percent_change_1h,
+            SortKey::H24 => value.percent_change_24h,
+            SortKey::D7 => value.percent_change_7d,
+            SortKey::D30 => value.percent_change_30d,
+            SortKey::MarketCap => value.market_cap,
+            SortKey::Volume24h => value.volume_24h,
+            SortKey::CirculatingSupply => self.circulating_supply.unwrap_or(f64::NAN),
+            SortKey::LocalHighDistance => {
+                self.local_high_distance(ctx.local_high).unwrap_or(f64::NAN)
+            }
+            SortKey::Holdings => self.holding_value(ctx.holdings).unwrap_or(f64::NAN),
+        }
+    }
+
+    // in percent, 0 at the highest price seen on this device rather than the all time high
+    pub fn local_high_distance(&self, local_high: &HashMap<u64, f64>) -> Option<f64> {
+        let high = local_high.get(&self.id).copied().unwrap_or_default();
+        (high > 0.).then(|| (self.quote.value.price / high - 1.) * 100.)
+    }
+
+    pub fn holding_value(&self, holdings: &HashMap<u64, f64>) -> Option<f64> {
+        holdings
+            .get(&self.id)
+            .map(|amount| amount * self.quote.value.price)
+    }
+}
+
+// curl -H "X-CMC_PRO_API_KEY: $API_KEY" -H "Accept: application/json" -d "start=1&limit=100&convert=USD&aux=cmc_rank,circulating_supply,tags" -G https://pro-api.coinmarketcap.com/v1/cryptocurrency/listings/latest
+pub async fn fetch(
+    client: &reqwest::Client,
+    api_base: &str,
+    api_key: &str,
+    currency: &str,
+    limit: u32,
+    save_path: &Path,
+) -> Result<Latest> {
+    let resp = client
+        .get(format!("{api_base}/v1/cryptocurrency/listings/latest"))
+        .headers(headers(api_key))
+        .query(&[
+            ("start", "1"),
+            ("limit", &limit.to_string()),
+            ("convert", currency),
+            ("aux", "cmc_rank,circulating_supply,tags"),
+        ])
+        .send()
+        .await?
+        .json::<Latest>()
+        .await?;
+
+    if resp.status.error_message.is_none() {
+        _ = cache::save(save_path, &resp);
+    }
+
+    Ok(resp)
+}
+
+pub(crate) fn headers(api_key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, "application/json".parse().unwrap());
+    headers.insert("X-CMC_PRO_API_KEY", api_key.parse().unwrap());
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    async fn fetch_from(api_base: &str, save_path: &Path) -> Result<Latest> {
+        let client = reqwest::Client::new();
+        fetch(&client, api_base, "test-key", "USD", 5, save_path).await
+    }
+
+    #[tokio::test]
+    async fn fetch_recorded_listings() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("latest.json");
+
+        let latest = fetch_from(&server.cmc_url(), &save_path).await.unwrap();
+        assert!(latest.status.error_message.is_none());
+        assert_eq!(latest.data.len(), 5);
+        assert_eq!(latest.data[0].symbol, "BTC");
+        assert_eq!(latest.data[0].quote.currency, "USD");
+        assert!(latest.data[0].quote.value.price > 0.);
+        assert!(latest.data[0].quote.value.market_cap > 0.);
+        assert!(latest.data[0].circulating_supply.is_some());
+        assert!(save_path.exists());
+
+        let req = &server.requests()[0];
+        assert_eq!(req.path, test_support::LISTINGS_PATH);
+        assert!(req.query.contains("limit=5"));
+        assert!(req.query.contains("convert=USD"));
+        assert_eq!(req.headers["x-cmc_pro_api_key"], "test-key");
+    }
+
+    #[tokio::test]
+    async fn fetch_invalid_key() {
+        let server = MockServer::start();
+        server.route_cmc_error();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("latest.json");
+
+        let latest = fetch_from(&server.cmc_url(), &save_path).await.unwrap();
+        assert_eq!(
+            latest.status.error_message.as_deref(),
+            Some("This API Key is invalid.")
+        );
+        assert!(latest.data.is_empty());
+        assert!(!save_path.exists());
+    }
+
+    #[tokio::test]
+    async fn fetch_rate_limited() {
+        let server = MockServer::start();
+        server.route_cmc_rate_limit();
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("latest.json");
+
+        let latest = fetch_from(&server.cmc_url(), &save_path).await.unwrap();
+        assert!(latest.status.error_message.unwrap().contains("rate limit"));
+        assert!(!save_path.exists());
+    }
+
+    #[tokio::test]
+    async fn fetch_unreachable() {
+        let dir = test_support::temp_dir();
+        let save_path = dir.join("latest.json");
+
+        let e = fetch_from(&test_support::unreachable_url(), &save_path)
+            .await
+            .unwrap_err();
+        assert!(util::is_network_error(&e));
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+        let dir = test_support::temp_dir();
+        let path = dir.join("latest.json");
+        cache::save(&path, &latest).unwrap();
+
+        let cached = cache::load::<Latest>(&path).unwrap();
+        assert_eq!(cached.data.len(), latest.data.len());
+        assert_eq!(cached.data[1].quote.currency, "USD");
+        assert_eq!(cached.status.timestamp, latest.status.timestamp);
+    }
+
+    #[test]
+    fn sort_values_of_external_data() {
+        let latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+        let (btc, eth) = (&latest.data[0], &latest.data[1]);
+        let marker_symbols = HashSet::from(["ETH".to_string()]);
+        let holdings = HashMap::from([(eth.id, 2.)]);
+        let local_high = HashMap::from([(btc.id, btc.quote.value.price * 2.)]);
+        let ctx = SortContext {
+            marker_symbols: &marker_symbols,
+            holdings: &holdings,
+            local_high: &local_high,
+        };
+
+        assert_eq!(eth.sort_value(SortKey::Marker, &ctx), 1.);
+        assert_eq!(
+            eth.sort_value(SortKey::Holdings, &ctx),
+            eth.quote.value.price * 2.
+        );
+        assert!(btc.sort_value(SortKey::Holdings, &ctx).is_nan());
+        assert_eq!(btc.sort_value(SortKey::LocalHighDistance, &ctx), -50.);
+        assert!(eth.sort_value(SortKey::LocalHighDistance, &ctx).is_nan());
+        assert_eq!(
+            btc.sort_value(SortKey::CirculatingSupply, &ctx),
+            19_642_350.
+        );
+    }
+
+    #[test]
+    fn sort_by_several_keys() {
+        let mut latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+        latest.data[2].quote.value.percent_change_24h = f64::NAN;
+        let ids = |latest: &Latest| latest.data.iter().map(|v| v.id).collect::<Vec<_>>();
+
+        let marker_symbols = HashSet::from(["SOL".to_string(), "BNB".to_string()]);
+        let (holdings, local_high) = (HashMap::new(), HashMap::new());
+        let ctx = SortContext {
+            marker_symbols: &marker_symbols,
+            holdings: &holdings,
+            local_high: &local_high,
+        };
+
+        latest.sort(&[SortSpec::asc(SortKey::Marker)], &ctx);
+        assert_eq!(ids(&latest), vec![1839, 5426, 1, 1027, 825]);
+
+        // the unknown change of USDT stays last in both directions
+        let mut h24 = SortSpec::asc(SortKey::H24);
+        latest.sort(&[h24], &ctx);
+        assert_eq!(latest.data.last().unwrap().id, 825);
+        h24.is_descending = true;
+        latest.sort(&[h24], &ctx);
+        assert_eq!(latest.data.last().unwrap().id, 825);
+
+        latest.sort(&[SortSpec::asc(SortKey::Marker), h24], &ctx);
+        assert_eq!(ids(&latest), vec![1839, 5426, 1027, 1, 825]);
+    }
+}
@@ -0,0 +1,27 @@
+// Data models, upstream fetchers, cache, config and formatting shared by the
+// picon front-end and any backend service, without the egui/wgpu stack.
+
+#[macro_use]
+extern crate serde_derive;
+
+pub mod backup;
+pub mod breadth;
+pub mod cache;
+pub mod compare;
+pub mod config;
+pub mod convert;
+pub mod economy;
+pub mod export;
+pub mod fees;
+pub mod format;
+pub mod global;
+pub mod history;
+pub mod icons;
+pub mod latest;
+pub mod service;
+pub mod stats;
+pub mod util;
+mod xlsx;
+
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
@@ -0,0 +1,300 @@
+use super::{
+    config::FeeEndpoint,
+    convert::{self, Rates},
+    economy::{self, Economy, EconomySource, InstrumentId},
+    fees::{self, Fees},
+    global::{self, GlobalMetrics},
+    icons,
+    latest::{self, Latest},
+    stats::{self, Stats},
+    util,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::{self, Runtime};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::AbortHandle;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Notify = Arc<dyn Fn() + Send + Sync>;
+
+pub type ResponseReceiver = UnboundedReceiver<Response>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    Latest {
+        api_base: String,
+        api_key: String,
+        currency: String,
+        limit: u32,
+        save_path: PathBuf,
+    },
+    Stats {
+        api_base: String,
+        greed_fear_base: String,
+        greed_fear_limit: u32,
+        save_path: PathBuf,
+        greed_fear_path: PathBuf,
+    },
+    Icon {
+        api_base: String,
+        dir: PathBuf,
+        coin_id: u64,
+    },
+    Fees {
+        endpoints: Vec<FeeEndpoint>,
+        save_path: PathBuf,
+    },
+    Global {
+        api_base: String,
+        api_key: String,
+        save_path: PathBuf,
+    },
+    Economy {
+        source: EconomySource,
+        api_base: String,
+        instruments: Vec<InstrumentId>,
+        save_path: PathBuf,
+    },
+    Convert {
+        api_base: String,
+        api_key: String,
+        from: String,
+        targets: Vec<String>,
+        save_path: PathBuf,
+    },
+}
+
+// every coin has its own icon request, so they do not abort each other
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Latest,
+    Stats,
+    Icon(u64),
+    Fees,
+    Global,
+    Economy,
+    Convert,
+}
+
+#[derive(Clone, Debug)]
+pub enum ResponseItem {
+    Latest(Latest),
+    Stats(Stats),
+    // `None` when the upstream has no logo of the coin
+    Icon(Option<Vec<u8>>),
+    Fees(Fees),
+    Global(GlobalMetrics),
+    Economy(Economy),
+    Convert(Rates),
+}
+
+#[derive(Clone, Debug)]
+pub enum FetchError {
+    // the upstream could not be reached at all
+    Offline(String),
+    Other(String),
+}
+
+// returned for every request, the UI keeps it to cancel the request
+#[derive(Clone, Debug)]
+pub struct FetchHandle {
+    pub id: u64,
+    pub req: Request,
+    abort: AbortHandle,
+}
+
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub id: u64,
+    pub kind: RequestKind,
+    pub result: Result<ResponseItem, FetchError>,
+}
+
+impl Request {
+    pub fn kind(&self) -> RequestKind {
+        match self {
+            Request::Latest { .. } => RequestKind::Latest,
+            Request::Stats { .. } => RequestKind::Stats,
+            Request::Icon { coin_id, .. } => RequestKind::Icon(*coin_id),
+            Request::Fees { .. } => RequestKind::Fees,
+            Request::Global { .. } => RequestKind::Global,
+            Request::Economy { .. } => RequestKind::Economy,
+            Request::Convert { .. } => RequestKind::Convert,
+        }
+    }
+}
+
+impl FetchHandle {
+    pub fn cancel(&self) {
+        self.abort.abort();
+    }
+}
+
+impl From<anyhow::Error> for FetchError {
+    fn from(e: anyhow::Error) -> Self {
+        if util::is_network_error(&e) {
+            FetchError::Offline(e.to_string())
+        } else {
+            FetchError::Other(e.to_string())
+        }
+    }
+}
+
+// Runs every fetch on a tokio runtime and delivers the results through an unbounded
+// channel, so nothing is dropped and no fetch waits for the UI to draw a frame.
+pub struct Service {
+    rt: Runtime,
+    client: reqwest::Client,
+    tx: UnboundedSender<Response>,
+    tasks: Arc<Mutex<HashMap<RequestKind, (u64, AbortHandle)>>>,
+    next_id: AtomicU64,
+    notify: Arc<Mutex<Option<Notify>>>,
+}
+
+impl Service {
+    pub fn new() -> (Self, ResponseReceiver) {
+        let rt = runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("picon-service")
+            .enable_all()
+            .build()
+            .expect("failed to build the tokio runtime");
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        (
+            Self {
+                rt,
+                client,
+                tx,
+                tasks: Arc::default(),
+                next_id: AtomicU64::new(1),
+                notify: Arc::default(),
+            },
+            rx,
+        )
+    }
+
+    // called after every response is queued, e.g. to request a repaint
+    pub fn on_response(&self, f: impl Fn() + Send + Sync + 'static) {
+        *self.notify.lock().unwrap() = Some(Arc::new(f));
+    }
+
+    // a new request aborts the running request of the same kind
+    pub fn send(&self, req: Request) -> FetchHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let kind = req.kind();
+        let task_req = req.clone();
+
+        let (client, tx, tasks, notify) = (
+            self.client.clone(),
+            self.tx.clone(),
+            self.tasks.clone(),
+            self.notify.clone(),
+        );
+
+        let mut running = self.tasks.lock().unwrap();
+        if let Some((_, handle)) = running.remove(&kind) {
+            handle.abort();
+        }
+
+        let handle = self.rt.spawn(async move {
+            let result = match task_req {
+                Request::Latest {
+                    api_base,
+                    api_key,
+                    currency,
+                    limit,
+                    save_path,
+                } => latest::fetch(&client, &api_base, &api_key, &currency, limit, &save_path)
+                    .await
+                    .map(ResponseItem::Latest),
+                Request::Stats {
+                    api_base,
+                    greed_fear_base,
+                    greed_fear_limit,
+                    save_path,
+                    greed_fear_path,
+                } => stats::fetch(
+                    &client,
+                    &api_base,
+                    &greed_fear_base,
+                    greed_fear_limit,
+                    &save_path,
+                    &greed_fear_path,
+                )
+                .await
+                .map(ResponseItem::Stats),
+                Request::Icon {
+                    api_base,
+                    dir,
+                    coin_id,
+                } => icons::fetch(&client, &api_base, &dir, coin_id)
+                    .await
+                    .map(ResponseItem::Icon),
+                Request::Fees {
+                    endpoints,
+                    save_path,
+                } => fees::fetch(&client, &endpoints, &save_path)
+                    .await
+                    .map(ResponseItem::Fees),
+                Request::Global {
+                    api_base,
+                    api_key,
+                    save_path,
+                } => global::fetch(&client, &api_base, &api_key, &save_path)
+                    .await
+                    .map(ResponseItem::Global),
+                Request::Economy {
+                    source,
+                    api_base,
+                    instruments,
+                    save_path,
+                } => economy::fetch(&client, source, &api_base, &instruments, &save_path)
+                    .await
+                    .map(ResponseItem::Economy),
+                Request::Convert {
+                    api_base,
+                    api_key,
+                    from,
+                    targets,
+                    save_path,
+                } => convert::fetch(&client, &api_base, &api_key, &from, &targets, &save_path)
+                    .await
+                    .map(ResponseItem::Convert),
+            };
+
+            {
+                let mut running = tasks.lock().unwrap();
+                if matches!(running.get(&kind), Some((v, _)) if *v == id) {
+                    running.remove(&kind);
+                }
+            }
+
+            _ = tx.send(Response {
+                id,
+                kind,
+                result: result.map_err(FetchError::from),
+            });
+
+            if let Some(f) = notify.lock().unwrap().as_ref() {
+                f();
+            }
+        });
+
+        let abort = handle.abort_handle();
+        running.insert(kind, (id, abort.clone()));
+
+        FetchHandle { id, req, abort }
+    }
+}
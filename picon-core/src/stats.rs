@@ -0,0 +1,307 @@
+use crate::history::DAY_SECS;
+use crate::{cache, util};
+use anyhow::Result;
+use std::path::Path;
+
+// the greed & fear points further than this from the requested day are not compared
+const GREED_FEAR_TOLERANCE_SECS: i64 = 2 * DAY_SECS;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Stats {
+    #[serde(skip)]
+    pub errors: Vec<String>,
+
+    // at least one upstream could not be reached
+    #[serde(skip)]
+    pub is_offline: bool,
+
+    pub crypto: Crypto,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Crypto {
+    pub greed_fear: GreedFear,
+    pub gas_fee: GasFee,
+}
+
+// one point per day, the newest first
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GreedFear {
+    pub data: Vec<GreedFearData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GreedFearData {
+    pub value: String,
+    pub timestamp: String,
+
+    #[serde(default)]
+    pub value_classification: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GreedFearLevel {
+    ExtremeFear,
+    Fear,
+    Neutral,
+    Greed,
+    ExtremeGreed,
+}
+
+impl GreedFearLevel {
+    // the inclusive upper bound of every band, from the lowest
+    pub const BANDS: [(u32, GreedFearLevel); 5] = [
+        (24, GreedFearLevel::ExtremeFear),
+        (46, GreedFearLevel::Fear),
+        (54, GreedFearLevel::Neutral),
+        (75, GreedFearLevel::Greed),
+        (100, GreedFearLevel::ExtremeGreed),
+    ];
+
+    pub fn from_value(value: u32) -> Self {
+        Self::BANDS
+            .iter()
+            .find(|(upper, _)| value <= *upper)
+            .map(|(_, level)| *level)
+            .unwrap_or(GreedFearLevel::ExtremeGreed)
+    }
+}
+
+impl GreedFearData {
+    pub fn point(&self) -> Option<(i64, u32)> {
+        Some((self.timestamp.parse().ok()?, self.value.parse().ok()?))
+    }
+}
+
+impl GreedFear {
+    // the points of `other` replace the ones of the same day
+    pub fn merge(&mut self, other: &GreedFear) {
+        let days = other
+            .data
+            .iter()
+            .filter_map(|v| v.point())
+            .map(|(ts, _)| ts.div_euclid(DAY_SECS))
+            .collect::<Vec<_>>();
+
+        self.data.retain(|v| match v.point() {
+            Some((ts, _)) => !days.contains(&ts.div_euclid(DAY_SECS)),
+            None => false,
+        });
+        self.data
+            .extend(other.data.iter().filter(|v| v.point().is_some()).cloned());
+        self.data
+            .sort_by_key(|v| std::cmp::Reverse(v.point().map(|p| p.0)));
+    }
+
+    // (utc seconds, value) in time order
+    pub fn points(&self) -> Vec<(i64, u32)> {
+        let mut points = self
+            .data
+            .iter()
+            .filter_map(|v| v.point())
+            .collect::<Vec<_>>();
+        points.sort_by_key(|p| p.0);
+        points
+    }
+
+    pub fn latest(&self) -> Option<(i64, u32)> {
+        self.data
+            .iter()
+            .filter_map(|v| v.point())
+            .max_by_key(|p| p.0)
+    }
+
+    // the value `secs` before the latest point, None when there is no point near that day
+    pub fn value_before(&self, secs: i64) -> Option<u32> {
+        let (latest, _) = self.latest()?;
+        let target = latest - secs;
+
+        self.data
+            .iter()
+            .filter_map(|v| v.point())
+            .filter(|p| p.0 <= target && target - p.0 < GREED_FEAR_TOLERANCE_SECS)
+            .max_by_key(|p| p.0)
+            .map(|p| p.1)
+    }
+}
+
+// the EVM chains are priced by `fees`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GasFee {
+    pub bitcoin: (u64, u64, u64),
+}
+
+// The greed & fear series is merged into the one stored at `greed_fear_path`, so the
+// local history keeps growing beyond `greed_fear_limit` days.
+pub async fn fetch(
+    client: &reqwest::Client,
+    api_base: &str,
+    greed_fear_base: &str,
+    greed_fear_limit: u32,
+    save_path: &Path,
+    greed_fear_path: &Path,
+) -> Result<Stats> {
+    let mut stats = Stats::default();
+    let (crypto, greed_fear) = tokio::join!(
+        fetch_crypto(client, api_base),
+        fetch_greed_fear(client, greed_fear_base, greed_fear_limit)
+    );
+
+    let crypto_ok = crypto.is_ok();
+    match crypto {
+        Ok(v) => stats.crypto = v,
+        Err(e) => {
+            stats.is_offline |= util::is_network_error(&e);
+            stats
+                .errors
+                .push(format!("fetch crypto stats error: {e:?}"));
+        }
+    }
+
+    let mut history = cache::load::<GreedFear>(greed_fear_path).unwrap_or_default();
+    history.merge(&stats.crypto.greed_fear);
+
+    match greed_fear {
+        Ok(v) => history.merge(&v),
+        Err(e) => {
+            stats.is_offline |= util::is_network_error(&e);
+            stats.errors.push(format!("fetch greed fear error: {e:?}"));
+        }
+    }
+
+    if !history.data.is_empty() {
+        _ = cache::save(greed_fear_path, &history);
+    }
+    stats.crypto.greed_fear = history;
+
+    // the greed & fear history has its own file, so only the crypto stats decide
+    if crypto_ok {
+        _ = cache::save(save_path, &stats);
+    }
+
+    Ok(stats)
+}
+
+pub async fn fetch_crypto(client: &reqwest::Client, api_base: &str) -> Result<Crypto> {
+    Ok(client
+        .get(format!("{api_base}/cryptocurrency/stats"))
+        .send()
+        .await?
+        .json::<Crypto>()
+        .await?)
+}
+
+pub async fn fetch_greed_fear(
+    client: &reqwest::Client,
+    api_base: &str,
+    limit: u32,
+) -> Result<GreedFear> {
+    Ok(client
+        .get(format!("{api_base}/fng/"))
+        .query(&[("limit", limit)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<GreedFear>()
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    async fn fetch_into(server_url: &str, greed_fear_url: &str, dir: &Path) -> Stats {
+        let (save_path, greed_fear_path) = (dir.join("stats.json"), dir.join("greed_fear.json"));
+        let client = reqwest::Client::new();
+        fetch(
+            &client,
+            server_url,
+            greed_fear_url,
+            30,
+            &save_path,
+            &greed_fear_path,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetch_recorded_stats() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+
+        let stats = fetch_into(&server.apisvr_url(), &server.cmc_url(), &dir).await;
+        assert!(stats.errors.is_empty());
+        assert!(!stats.is_offline);
+        assert_eq!(stats.crypto.greed_fear.data.len(), 10);
+        assert_eq!(stats.crypto.greed_fear.latest(), Some((1709337600, 80)));
+        assert_eq!(stats.crypto.gas_fee.bitcoin, (32, 41, 52));
+        assert!(dir.join("stats.json").exists());
+        assert!(dir.join("greed_fear.json").exists());
+
+        let query = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == test_support::GREED_FEAR_PATH)
+            .unwrap()
+            .query;
+        assert_eq!(query, "limit=30");
+    }
+
+    #[tokio::test]
+    async fn fetch_server_error() {
+        let server = MockServer::recorded();
+        server.route(test_support::GREED_FEAR_PATH, 500, "internal error");
+        let dir = test_support::temp_dir();
+
+        let stats = fetch_into(&server.apisvr_url(), &server.cmc_url(), &dir).await;
+        assert_eq!(stats.errors.len(), 1);
+        assert!(!stats.is_offline);
+        assert_eq!(stats.crypto.gas_fee.bitcoin, (32, 41, 52));
+        assert_eq!(stats.crypto.greed_fear.data.len(), 2);
+        assert!(dir.join("stats.json").exists());
+        assert!(dir.join("greed_fear.json").exists());
+    }
+
+    #[tokio::test]
+    async fn fetch_unreachable() {
+        let dir = test_support::temp_dir();
+        let url = test_support::unreachable_url();
+
+        let stats = fetch_into(&url, &url, &dir).await;
+        assert_eq!(stats.errors.len(), 2);
+        assert!(stats.is_offline);
+    }
+
+    #[tokio::test]
+    async fn greed_fear_history_grows() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let old = GreedFear {
+            data: vec![GreedFearData {
+                value: "10".to_string(),
+                timestamp: (1709337600 - 365 * DAY_SECS).to_string(),
+                ..Default::default()
+            }],
+        };
+        cache::save(&dir.join("greed_fear.json"), &old).unwrap();
+
+        let stats = fetch_into(&server.apisvr_url(), &server.cmc_url(), &dir).await;
+        let greed_fear = &stats.crypto.greed_fear;
+        assert_eq!(greed_fear.data.len(), 11);
+        assert_eq!(greed_fear.value_before(DAY_SECS), Some(82));
+        assert_eq!(greed_fear.value_before(7 * DAY_SECS), Some(70));
+        assert_eq!(greed_fear.value_before(30 * DAY_SECS), None);
+        assert_eq!(greed_fear.value_before(365 * DAY_SECS), Some(10));
+    }
+
+    #[test]
+    fn greed_fear_levels() {
+        assert_eq!(GreedFearLevel::from_value(0), GreedFearLevel::ExtremeFear);
+        assert_eq!(GreedFearLevel::from_value(25), GreedFearLevel::Fear);
+        assert_eq!(GreedFearLevel::from_value(50), GreedFearLevel::Neutral);
+        assert_eq!(GreedFearLevel::from_value(75), GreedFearLevel::Greed);
+        assert_eq!(GreedFearLevel::from_value(80), GreedFearLevel::ExtremeGreed);
+    }
+}
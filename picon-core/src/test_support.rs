@@ -0,0 +1,253 @@
+// A local HTTP stub for the CMC, apisvr, greed & fear, Yahoo and JSON-RPC upstreams, serving the recorded
+// responses in `tests/fixtures` so fetch paths can be tested without network.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const CMC_LISTINGS_LATEST: &str = include_str!("../tests/fixtures/cmc_listings_latest.json");
+pub const CMC_LISTINGS_ERROR: &str = include_str!("../tests/fixtures/cmc_listings_error.json");
+pub const CMC_RATE_LIMIT: &str = include_str!("../tests/fixtures/cmc_rate_limit.json");
+pub const CMC_GLOBAL_METRICS: &str = include_str!("../tests/fixtures/cmc_global_metrics.json");
+pub const CMC_PRICE_CONVERSION: &str = include_str!("../tests/fixtures/cmc_price_conversion.json");
+pub const APISVR_MARKET_LATEST: &str = include_str!("../tests/fixtures/apisvr_market_latest.json");
+pub const APISVR_CRYPTO_STATS: &str = include_str!("../tests/fixtures/apisvr_crypto_stats.json");
+pub const ALTERNATIVE_FNG: &str = include_str!("../tests/fixtures/alternative_fng.json");
+pub const RPC_ETH_FEE_HISTORY: &str = include_str!("../tests/fixtures/rpc_eth_fee_history.json");
+pub const RPC_ETH_GAS_PRICE: &str = include_str!("../tests/fixtures/rpc_eth_gas_price.json");
+pub const RPC_METHOD_NOT_FOUND: &str = include_str!("../tests/fixtures/rpc_method_not_found.json");
+pub const YAHOO_CHART_GOLD: &str = include_str!("../tests/fixtures/yahoo_chart_gold.json");
+pub const RPC_SOL_PRIORITY_FEES: &str =
+    include_str!("../tests/fixtures/rpc_sol_priority_fees.json");
+
+pub const LISTINGS_PATH: &str = "/v1/cryptocurrency/listings/latest";
+pub const GLOBAL_METRICS_PATH: &str = "/v1/global-metrics/quotes/latest";
+pub const PRICE_CONVERSION_PATH: &str = "/v2/tools/price-conversion";
+pub const MARKET_PATH: &str = "/apisvr/market/latest";
+pub const CRYPTO_STATS_PATH: &str = "/apisvr/cryptocurrency/stats";
+pub const GREED_FEAR_PATH: &str = "/fng/";
+pub const RPC_EVM_PATH: &str = "/rpc/evm";
+pub const RPC_SOLANA_PATH: &str = "/rpc/solana";
+
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub path: String,
+    pub query: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<HashMap<String, MockResponse>>>,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    // an empty server, every path answers 404 until it is routed
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes: Arc<Mutex<HashMap<String, MockResponse>>> = Arc::default();
+        let requests: Arc<Mutex<Vec<MockRequest>>> = Arc::default();
+
+        let (r, q) = (routes.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (r, q) = (r.clone(), q.clone());
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, &r, &q) {
+                        log::debug!("{e:?}");
+                    }
+                });
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            requests,
+        }
+    }
+
+    // serves the successful recorded response of every upstream
+    pub fn recorded() -> Self {
+        let server = Self::start();
+        server.route(LISTINGS_PATH, 200, CMC_LISTINGS_LATEST);
+        server.route(GLOBAL_METRICS_PATH, 200, CMC_GLOBAL_METRICS);
+        server.route(PRICE_CONVERSION_PATH, 200, CMC_PRICE_CONVERSION);
+        server.route(MARKET_PATH, 200, APISVR_MARKET_LATEST);
+        server.route(CRYPTO_STATS_PATH, 200, APISVR_CRYPTO_STATS);
+        server.route(GREED_FEAR_PATH, 200, ALTERNATIVE_FNG);
+        server.route(RPC_EVM_PATH, 200, RPC_ETH_FEE_HISTORY);
+        server.route(RPC_SOLANA_PATH, 200, RPC_SOL_PRIORITY_FEES);
+        server
+    }
+
+    pub fn route(&self, path: &str, status: u16, body: &str) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            MockResponse {
+                status,
+                body: body.to_string(),
+            },
+        );
+    }
+
+    // a JSON-RPC endpoint answering `method` at `path`, the other methods fall back to `route`
+    pub fn route_rpc(&self, path: &str, method: &str, body: &str) {
+        self.route(&format!("{path}#{method}"), 200, body);
+    }
+
+    // the CMC error responses, e.g. an invalid api key or a rate limit
+    pub fn route_cmc_error(&self) {
+        self.route(LISTINGS_PATH, 401, CMC_LISTINGS_ERROR);
+    }
+
+    pub fn route_cmc_rate_limit(&self) {
+        self.route(LISTINGS_PATH, 429, CMC_RATE_LIMIT);
+    }
+
+    pub fn cmc_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn apisvr_url(&self) -> String {
+        format!("http://{}/apisvr", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+// an address nothing listens on, requests to it fail to connect
+pub fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}")
+}
+
+// a fresh directory under the system temp dir, removed with its files when dropped
+pub struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn temp_dir() -> TempDir {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "picon-test-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
+
+fn serve(
+    stream: TcpStream,
+    routes: &Mutex<HashMap<String, MockResponse>>,
+    requests: &Mutex<Vec<MockRequest>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let target = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((k, v)) = line.split_once(':') {
+            headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or_default();
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    let method = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v["method"].as_str().map(|v| v.to_string()))
+        .unwrap_or_default();
+
+    requests.lock().unwrap().push(MockRequest {
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    });
+
+    let routes = routes.lock().unwrap();
+    let resp = routes
+        .get(&format!("{path}#{method}"))
+        .or_else(|| routes.get(path))
+        .cloned()
+        .unwrap_or(MockResponse {
+            status: 404,
+            body: "not found".to_string(),
+        });
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        resp.status,
+        reason(resp.status),
+        resp.body.len(),
+        resp.body
+    )?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+
+pub fn timestamp() -> i64 {
+    Utc::now().timestamp()
+}
+
+pub fn timelapse(s: i64) -> i64 {
+    timestamp() - s
+}
+
+// parse the RFC 3339 timestamps returned by the upstream APIs, e.g. "2024-03-02T05:35:51.885Z"
+pub fn utc_seconds_from_str(time: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.timestamp())
+}
+
+// errors which mean the upstream could not be reached at all
+pub fn is_network_error(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| match c.downcast_ref::<reqwest::Error>() {
+        Some(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        None => false,
+    })
+}
+
+pub fn short_time(s: i64) -> String {
+    match s {
+        s if s > 3600 * 24 => format!("{}d", s / (3600 * 24)),
+        s if s > 3600 => format!("{}h", s / 3600),
+        s if s > 60 => format!("{}m", s / 60),
+        _ => format!("{s}s"),
+    }
+}
+
+// how long ago the upstream produced the data, "-" when it is unknown
+pub fn data_age(timestamp: i64) -> String {
+    if timestamp <= 0 {
+        return "-".to_string();
+    }
+
+    short_time(timelapse(timestamp))
+}
@@ -0,0 +1,175 @@
+// A minimal SpreadsheetML writer: one sheet per table, strings inline, in a zip
+// archive with stored entries, which every spreadsheet application reads.
+use crate::export::{Cell, Table};
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::{Seek, Write};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 0 -> "A", 26 -> "AA"
+fn column_ref(mut index: usize) -> String {
+    let mut name = vec![];
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn cell_xml(out: &mut String, column: usize, row: usize, cell: &Cell) {
+    let r = format!("{}{}", column_ref(column), row + 1);
+    match cell {
+        Cell::Number(v) if v.is_finite() => _ = write!(out, r#"<c r="{r}"><v>{v}</v></c>"#),
+        Cell::Number(_) => (),
+        Cell::Text(text) => {
+            _ = write!(
+                out,
+                r#"<c r="{r}" t="inlineStr"><is><t>{}</t></is></c>"#,
+                escape(text)
+            )
+        }
+    }
+}
+
+fn sheet_xml(table: &Table) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    );
+
+    let header = table.header.iter().map(|v| Cell::Text(v.clone()));
+    let header = header.collect::<Vec<_>>();
+    for (row, cells) in std::iter::once(&header)
+        .chain(table.rows.iter())
+        .enumerate()
+    {
+        _ = write!(out, r#"<row r="{}">"#, row + 1);
+        for (column, cell) in cells.iter().enumerate() {
+            cell_xml(&mut out, column, row, cell);
+        }
+        out.push_str("</row>");
+    }
+
+    out.push_str("</sheetData></worksheet>");
+    out
+}
+
+fn parts(tables: &[Table]) -> Vec<(String, String)> {
+    let mut content_types = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    );
+    let mut workbook = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    );
+    let mut workbook_rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    let mut sheets = vec![];
+
+    for (i, table) in tables.iter().enumerate() {
+        let n = i + 1;
+        _ = write!(
+            content_types,
+            r#"<Override PartName="/xl/worksheets/sheet{n}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        );
+        _ = write!(
+            workbook,
+            r#"<sheet name="{}" sheetId="{n}" r:id="rId{n}"/>"#,
+            escape(&table.name)
+        );
+        _ = write!(
+            workbook_rels,
+            r#"<Relationship Id="rId{n}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{n}.xml"/>"#
+        );
+        sheets.push((format!("xl/worksheets/sheet{n}.xml"), sheet_xml(table)));
+    }
+
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    workbook_rels.push_str("</Relationships>");
+
+    let mut parts = vec![
+        ("[Content_Types].xml".to_string(), content_types),
+        (
+            "_rels/.rels".to_string(),
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#
+                .to_string(),
+        ),
+        ("xl/workbook.xml".to_string(), workbook),
+        ("xl/_rels/workbook.xml.rels".to_string(), workbook_rels),
+    ];
+    parts.extend(sheets);
+    parts
+}
+
+// the entries are stored without compression, so no deflate backend is needed
+pub fn write(tables: &[Table], out: impl Write + Seek) -> Result<()> {
+    let mut zip = ZipWriter::new(out);
+    let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, data) in parts(tables) {
+        zip.start_file(name, options)?;
+        zip.write_all(data.as_bytes())?;
+    }
+    // the finish on drop would swallow an error
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn column_names() {
+        assert_eq!(column_ref(0), "A");
+        assert_eq!(column_ref(25), "Z");
+        assert_eq!(column_ref(26), "AA");
+        assert_eq!(column_ref(27), "AB");
+    }
+
+    #[test]
+    fn zip_entries() {
+        let table = Table {
+            name: "latest".to_string(),
+            header: vec!["symbol".to_string(), "price".to_string()],
+            rows: vec![vec![Cell::Text("B&B".to_string()), Cell::Number(1.5)]],
+        };
+
+        let mut bytes = vec![];
+        write(std::slice::from_ref(&table), Cursor::new(&mut bytes)).unwrap();
+
+        // read back through the central directory, reading each entry to the end checks its crc
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let expected = parts(&[table]);
+        assert_eq!(archive.len(), expected.len());
+        for (i, (name, data)) in expected.iter().enumerate() {
+            let mut entry = archive.by_index(i).unwrap();
+            assert_eq!(entry.name(), name);
+            assert_eq!(entry.compression(), zip::CompressionMethod::Stored);
+
+            let mut text = String::new();
+            entry.read_to_string(&mut text).unwrap();
+            assert_eq!(&text, data);
+        }
+
+        let mut sheet = String::new();
+        archive
+            .by_name("xl/worksheets/sheet1.xml")
+            .unwrap()
+            .read_to_string(&mut sheet)
+            .unwrap();
+        assert!(sheet.contains("<t>B&amp;B</t>"));
+        assert!(sheet.contains(r#"<c r="B2"><v>1.5</v></c>"#));
+    }
+}
@@ -10,7 +10,7 @@ pub fn ui(app: &mut App, ui: &mut Ui) {
             Button::image_and_text(
                 app.back_icon.clone().unwrap().id(),
                 theme::BACK_ICON_SIZE,
-                RichText::new(tr(app.conf.ui.is_cn, "关于"))
+                RichText::new(tr(app.conf.ui.locale, "about"))
                     .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
             )
             .frame(false),
@@ -29,18 +29,10 @@ pub fn ui(app: &mut App, ui: &mut Ui) {
         ui.heading(title);
         ui.add_space(theme::SPACING);
 
-        if app.conf.ui.is_cn {
-            ui.label("基于egui。版权2022-2030 Heng30保留所有权利。该程序按原样提供，不提供任何形式的保证，包括设计，适销性和特定用途的保证。");
-        } else {
-            ui.label("Based on egui. Copyright 2022-2030. All rights reserved by Heng30. The program is provided AS IS with NO WARRANTY OF ANY KIND, INCLUDING THE WARRANTY OF DESIGN, MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE.");
-        }
+        ui.label(tr(app.conf.ui.locale, "about_license"));
 
         ui.add_space(theme::SPACING * 2.);
-        if app.conf.ui.is_cn {
-            ui.label("🎉❤给我买一杯咖啡(MetaMask)❤🎉");
-        } else {
-            ui.label("🎉❤Buy Me a Coffee(MetaMask)❤🎉");
-        }
+        ui.label(tr(app.conf.ui.locale, "about_coffee"));
 
         ui.add_space(theme::SPACING);
 
@@ -1,48 +1,54 @@
 use super::{
-    about, apikey,
-    config::Config,
-    latest::{self, Latest},
-    stats::{self, Stats},
-    theme,
-    tr::tr,
-    trending, util,
+    about, apikey, backup, breadth, compare, converter, dominance, economy, export, fees, latest,
+    setting, stats, theme, tr::tr, trending,
 };
 use egui::{
-    containers::Frame, Align, Button, Color32, Context, ImageButton, Layout, Pos2, RichText,
-    Stroke, TextureHandle, Ui, Window,
+    containers::Frame, Align, Button, Context, ImageButton, Layout, Pos2, RichText, Stroke,
+    TextureHandle, Ui, Window,
+};
+use picon_core::{
+    compare::MIN_COINS,
+    config::{Config, LatestView},
+    convert::Rates,
+    economy::{Economy, EconomyHistory, EconomySource},
+    fees::Fees,
+    global::{GlobalHistory, GlobalMetrics},
+    history::History,
+    latest::Latest,
+    service::{
+        FetchError, FetchHandle, Request, RequestKind, ResponseItem, ResponseReceiver, Service,
+    },
+    stats::Stats,
+    util,
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver, SyncSender};
 use std::sync::Arc;
+use std::time::Instant;
 
-#[allow(unused)]
-#[derive(Clone, Debug)]
-enum MsgType {
+#[derive(Clone, Debug, Default)]
+pub enum MsgType {
+    #[default]
     Info,
     Warn,
     Success,
+    #[allow(dead_code)]
     Danger,
 }
 
-impl Default for MsgType {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
 pub enum CurrentPanel {
+    #[default]
     Latest,
+    // no tab opens it until the trending upstream is implemented
+    #[allow(dead_code)]
     Trending,
     Stats,
+    Converter,
+    Compare,
     About,
-}
-
-impl Default for CurrentPanel {
-    fn default() -> Self {
-        Self::Latest
-    }
+    Setting,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -52,47 +58,66 @@ struct MsgSpec {
     timestamp: i64,
 }
 
-#[derive(Clone, Debug)]
-enum ChannelInnerItem {
-    Latest(Latest),
-    Trending(()),
-    Stats(Stats),
-}
-
-#[derive(Clone, Debug)]
-enum ChannelItem {
-    ErrMsg((CurrentPanel, String)),
-    Item(ChannelInnerItem),
-}
-
 #[derive(Clone, Default)]
 pub struct App {
     pub is_scroll_to_top_latest: bool,
     pub is_scroll_to_top_trending: bool,
     pub is_scroll_to_top_stats: bool,
 
-    pub is_fetching_latest: bool,
-    pub is_fetching_trending: bool,
-    pub is_fetching_stats: bool,
+    // the last fetch could not reach the upstream
+    pub is_network_down: bool,
 
     pub latest: Latest,
+
+    // price snapshots in the currency of `latest`
+    pub history: History,
+    #[allow(dead_code)]
     pub trending: (),
     pub stats: Stats,
 
+    // the last fee of every chain, kept when a later fetch of the chain fails
+    pub fees: Fees,
+
+    pub economy: Economy,
+    pub economy_history: EconomyHistory,
+
+    // from CMC, the dominance shifts are charted from the history
+    pub global: GlobalMetrics,
+    pub global_history: GlobalHistory,
+
+    // the fetched pairs the listings can not convert
+    pub rates: Rates,
+
     pub current_panel: CurrentPanel,
     pub prev_panel: CurrentPanel,
 
     pub conf: Config,
 
+    // resolved from `conf.ui.theme` and `conf.ui.color_convention`, applied to the egui style when it changes
+    pub theme: theme::Theme,
+    pub is_system_dark: Option<bool>,
+
+    #[allow(dead_code)]
     pub about_setting: about::Setting,
     pub latest_setting: latest::Setting,
+    pub breadth_setting: breadth::Setting,
+    pub converter_setting: converter::Setting,
+    pub compare_setting: compare::Setting,
+    pub export_setting: export::Setting,
+    pub backup_setting: backup::Setting,
     msg_spec: MsgSpec,
 
-    tx: Option<Arc<SyncSender<ChannelItem>>>,
-    rx: Option<Rc<RefCell<Receiver<ChannelItem>>>>,
+    service: Option<Arc<Service>>,
+    rx: Option<Rc<RefCell<ResponseReceiver>>>,
+
+    // the newest request of each kind, responses to older requests are discarded
+    pending_requests: HashMap<RequestKind, FetchHandle>,
 
     pub cmc_pro_api_key: String,
 
+    // uploaded when a row is first shown, keyed by the coin id
+    pub coin_icons: HashMap<u64, latest::CoinIcon>,
+
     brand_icon: Option<TextureHandle>,
     refresh_icon: Option<TextureHandle>,
     language_icon: Option<TextureHandle>,
@@ -108,8 +133,8 @@ pub struct App {
 impl App {
     pub fn new() -> Self {
         let mut app = App::default();
-        let (tx, rx) = mpsc::sync_channel(10);
-        (app.tx, app.rx) = (Some(Arc::new(tx)), Some(Rc::new(RefCell::new(rx))));
+        let (service, rx) = Service::new();
+        (app.service, app.rx) = (Some(Arc::new(service)), Some(Rc::new(RefCell::new(rx))));
         app.cmc_pro_api_key = apikey::CMC_PRO_API_KEY.to_string();
 
         app
@@ -120,6 +145,23 @@ impl App {
             log::warn!("{e:?}");
         }
 
+        if let Some(path) = self.conf.invalid_backup.clone() {
+            self.show_message(
+                format!(
+                    "{}\n{}",
+                    tr(self.conf.ui.locale, "config_invalid"),
+                    path.display()
+                ),
+                MsgType::Warn,
+            );
+        }
+
+        let repaint_ctx = ctx.clone();
+        self.service
+            .as_ref()
+            .unwrap()
+            .on_response(move || repaint_ctx.request_repaint());
+
         self.brand_icon = Some(ctx.load_texture(
             "brand-icon",
             theme::load_image_from_memory(theme::BRAND_ICON),
@@ -182,6 +224,10 @@ impl App {
 
         latest::init(self);
         stats::init(self);
+        fees::init(self);
+        economy::init(self);
+        dominance::init(self);
+        converter::init(self);
 
         self.fetch_latest();
         // self.fetch_stats();
@@ -193,9 +239,32 @@ impl App {
         self.current_panel = panel;
     }
 
+    // forced by the user or detected from the last fetch
+    pub fn is_offline(&self) -> bool {
+        self.conf.net.is_offline || self.is_network_down
+    }
+
+    pub fn is_stale(&self, timestamp: i64) -> bool {
+        timestamp <= 0 || util::timelapse(timestamp) > self.conf.net.stale_secs
+    }
+
+    fn apply_theme(&mut self, ctx: &Context) {
+        let theme = theme::Theme::from_mode(self.conf.ui.theme, self.is_system_dark)
+            .with_convention(self.conf.ui.color_convention);
+        if theme != self.theme {
+            theme::set_style(ctx, &theme);
+            self.theme = theme;
+        }
+    }
+
     pub fn ui(&mut self, ctx: &Context) {
+        self.apply_theme(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.current_panel != CurrentPanel::About {
+            if !matches!(
+                self.current_panel,
+                CurrentPanel::About | CurrentPanel::Setting | CurrentPanel::Compare
+            ) {
                 self.header(ui);
             }
 
@@ -203,30 +272,32 @@ impl App {
                 CurrentPanel::Latest => latest::ui(self, ui),
                 CurrentPanel::Trending => trending::ui(self, ui),
                 CurrentPanel::Stats => stats::ui(self, ui),
+                CurrentPanel::Converter => converter::ui(self, ui),
+                CurrentPanel::Compare => compare::ui(self, ui),
                 CurrentPanel::About => about::ui(self, ui),
+                CurrentPanel::Setting => setting::ui(self, ui),
             }
 
             self.update_data();
         });
 
+        latest::flush_markers(self, ctx);
+
         self.popup_message(ctx);
     }
 
     fn header(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-                ui.heading(
-                    RichText::new(tr(
-                        self.conf.ui.is_cn,
-                        match self.current_panel {
-                            CurrentPanel::Latest => "行情",
-                            CurrentPanel::Trending => "热门",
-                            CurrentPanel::Stats => "指数",
-                            _ => "",
-                        },
-                    ))
-                    .color(theme::BRAND_COLOR),
-                );
+                let locale = self.conf.ui.locale;
+                let title = match self.current_panel {
+                    CurrentPanel::Latest => tr(locale, "latest"),
+                    CurrentPanel::Trending => tr(locale, "trending"),
+                    CurrentPanel::Stats => tr(locale, "stats"),
+                    CurrentPanel::Converter => tr(locale, "converter"),
+                    _ => String::default(),
+                };
+                ui.heading(RichText::new(title).color(self.theme.brand_color));
             });
 
             // double-clicked-area to scroll to top
@@ -263,6 +334,35 @@ impl App {
                     self.switch_panel(CurrentPanel::About);
                 }
 
+                if ui
+                    .add(
+                        Button::new(
+                            RichText::new("⚙")
+                                .color(self.theme.brand_color)
+                                .size(theme::ICON_SIZE.y * 0.9),
+                        )
+                        .frame(false),
+                    )
+                    .clicked()
+                {
+                    self.switch_panel(CurrentPanel::Setting);
+                }
+
+                if ui
+                    .add(
+                        Button::new(
+                            RichText::new("⇄")
+                                .color(self.theme.brand_color)
+                                .size(theme::ICON_SIZE.y * 0.9),
+                        )
+                        .frame(false),
+                    )
+                    .on_hover_text(tr(self.conf.ui.locale, "converter"))
+                    .clicked()
+                {
+                    self.current_panel = CurrentPanel::Converter;
+                }
+
                 if ui
                     .add(
                         ImageButton::new(
@@ -274,6 +374,9 @@ impl App {
                     .clicked()
                 {
                     self.current_panel = CurrentPanel::Stats;
+                    if self.is_stale(self.global.last_updated) {
+                        self.fetch_stats();
+                    }
                 }
 
                 if ui
@@ -296,7 +399,7 @@ impl App {
                     )
                     .clicked()
                 {
-                    self.conf.ui.is_cn = !self.conf.ui.is_cn;
+                    self.conf.ui.locale = self.conf.ui.locale.next();
                     if let Err(e) = self.conf.save() {
                         log::warn!("{e:?}");
                     }
@@ -312,20 +415,99 @@ impl App {
                     match self.current_panel {
                         CurrentPanel::Latest => self.fetch_latest(),
                         CurrentPanel::Stats => self.fetch_stats(),
-                        CurrentPanel::Trending => todo!(),
+                        // the rates are crossed from the prices of the listings
+                        CurrentPanel::Converter => self.fetch_latest(),
+                        CurrentPanel::Trending => (),
                         _ => (),
                     }
                 }
 
-                let is_show_refresh_text = match self.current_panel {
-                    CurrentPanel::Latest => self.is_fetching_latest,
-                    CurrentPanel::Stats => self.is_fetching_stats,
-                    _ => false,
+                if self.current_panel == CurrentPanel::Latest {
+                    let (icon, view, key) = match self.conf.latest.view {
+                        LatestView::List => ("▦", LatestView::Heatmap, "heatmap"),
+                        LatestView::Heatmap => ("☰", LatestView::List, "list"),
+                    };
+
+                    if ui
+                        .add(
+                            Button::new(
+                                RichText::new(icon)
+                                    .color(self.theme.brand_color)
+                                    .size(theme::ICON_SIZE.y * 0.9),
+                            )
+                            .frame(false),
+                        )
+                        .on_hover_text(tr(self.conf.ui.locale, key))
+                        .clicked()
+                    {
+                        self.conf.latest.view = view;
+                        if let Err(e) = self.conf.save() {
+                            log::warn!("{e:?}");
+                        }
+                    }
+
+                    if ui
+                        .add(
+                            Button::new(
+                                RichText::new("⤓")
+                                    .color(self.theme.brand_color)
+                                    .size(theme::ICON_SIZE.y * 0.9),
+                            )
+                            .frame(false),
+                        )
+                        .on_hover_text(tr(self.conf.ui.locale, "export"))
+                        .clicked()
+                    {
+                        self.export_setting.is_open = true;
+                    }
+
+                    let count = self.compare_setting.coin_ids.len();
+                    if count >= MIN_COINS
+                        && ui
+                            .add(
+                                Button::new(
+                                    RichText::new(format!("⚖{count}"))
+                                        .color(self.theme.brand_color)
+                                        .size(theme::ICON_SIZE.y * 0.9),
+                                )
+                                .frame(false),
+                            )
+                            .on_hover_text(tr(self.conf.ui.locale, "compare"))
+                            .clicked()
+                    {
+                        self.switch_panel(CurrentPanel::Compare);
+                    }
+                }
+
+                // the converter is refreshed from the listings and fills the gaps with conversions
+                let kinds: &[RequestKind] = match self.current_panel {
+                    CurrentPanel::Latest => &[RequestKind::Latest],
+                    CurrentPanel::Stats => &[RequestKind::Stats],
+                    CurrentPanel::Converter => &[RequestKind::Latest, RequestKind::Convert],
+                    _ => &[],
                 };
+                let fetching_kinds = kinds
+                    .iter()
+                    .copied()
+                    .filter(|&kind| self.is_fetching(kind))
+                    .collect::<Vec<_>>();
+
+                if !fetching_kinds.is_empty() {
+                    let btn = Button::new(
+                        RichText::new(tr(self.conf.ui.locale, "cancel_refresh"))
+                            .color(self.theme.title_color),
+                    )
+                    .frame(false);
 
-                if is_show_refresh_text {
+                    if ui.add(btn).clicked() {
+                        for kind in fetching_kinds {
+                            self.cancel_fetch(kind);
+                        }
+                    }
+                } else if self.is_offline() {
                     ui.label(
-                        RichText::new(tr(self.conf.ui.is_cn, "正在刷新")).color(theme::TITLE_COLOR),
+                        RichText::new(tr(self.conf.ui.locale, "offline"))
+                            .color(self.theme.light_color),
                     );
                 }
             });
@@ -335,98 +517,208 @@ impl App {
     }
 
     fn update_data(&mut self) {
-        let rx = self.rx.clone();
-
-        if let Ok(item) = rx.unwrap().borrow_mut().try_recv() {
-            match item {
-                ChannelItem::ErrMsg((panel, msg)) => {
-                    match panel {
-                        CurrentPanel::Latest => self.is_fetching_latest = false,
-                        CurrentPanel::Trending => self.is_fetching_trending = false,
-                        CurrentPanel::Stats => self.is_fetching_stats = false,
-                        _ => (),
+        let rx = self.rx.clone().unwrap();
+
+        while let Ok(resp) = rx.borrow_mut().try_recv() {
+            if !matches!(self.pending_requests.get(&resp.kind), Some(h) if h.id == resp.id) {
+                continue;
+            }
+            self.pending_requests.remove(&resp.kind);
+
+            if let RequestKind::Icon(coin_id) = resp.kind {
+                match resp.result {
+                    // loaded from `conf.icons_dir` when the row is drawn again
+                    Ok(ResponseItem::Icon(Some(_))) => _ = self.coin_icons.remove(&coin_id),
+                    Ok(_) => _ = self.coin_icons.insert(coin_id, latest::CoinIcon::Missing),
+                    Err(e) => {
+                        log::debug!("{e:?}");
+                        self.coin_icons
+                            .insert(coin_id, latest::CoinIcon::Failed(Instant::now()));
                     }
-                    self.show_message(msg, MsgType::Warn);
                 }
-                ChannelItem::Item(item) => match item {
-                    ChannelInnerItem::Latest(item) => {
-                        if let Some(e) = item.status.error_message {
-                            self.show_message(e, MsgType::Warn);
-                        } else {
-                            if !item.data.is_empty() {
-                                self.latest = item;
-                                latest::update_addition_info(self);
-                                latest::sort_by_key(self, self.latest_setting.sort_key, false);
-                            }
-                        }
-                        self.is_fetching_latest = false;
+                continue;
+            }
+
+            if resp.kind == RequestKind::Convert
+                && !matches!(&resp.result, Ok(ResponseItem::Convert(item)) if item.errors.is_empty())
+            {
+                self.converter_setting.retry();
+            }
+
+            match resp.result {
+                Err(FetchError::Offline(msg)) => {
+                    self.is_network_down = true;
+                    log::warn!("{msg}");
+                    self.show_message(
+                        tr(self.conf.ui.locale, "network_unavailable"),
+                        MsgType::Warn,
+                    );
+                }
+                Err(FetchError::Other(msg)) => self.show_message(msg, MsgType::Warn),
+                Ok(ResponseItem::Latest(item)) => {
+                    self.is_network_down = false;
+                    if let Some(e) = item.status.error_message {
+                        self.show_message(e, MsgType::Warn);
+                    } else if !item.data.is_empty() {
+                        self.latest = item;
+                        self.latest.update_addition_info();
+                        latest::record_history(self);
+                        latest::retain_icons(self);
+                        latest::sort(self);
+                    }
+                }
+                Ok(ResponseItem::Icon(_)) => (),
+                Ok(ResponseItem::Fees(item)) => {
+                    if !item.errors.is_empty() {
+                        self.show_message(item.errors.join("\n\n"), MsgType::Warn);
+                    }
+                    self.fees.merge(item);
+                }
+                Ok(ResponseItem::Global(item)) => {
+                    self.global = item;
+                    dominance::record_history(self);
+                }
+                Ok(ResponseItem::Convert(item)) => {
+                    if !item.errors.is_empty() {
+                        self.show_message(item.errors.join("\n\n"), MsgType::Warn);
                     }
-                    ChannelInnerItem::Trending(_) => {
-                        self.is_fetching_trending = false;
-                        unimplemented!();
+                    self.rates.merge(item);
+                }
+                Ok(ResponseItem::Economy(item)) => {
+                    if !item.errors.is_empty() {
+                        self.show_message(item.errors.join("\n\n"), MsgType::Warn);
+                        self.economy.merge(&item);
+                    } else {
+                        self.economy = item.clone();
                     }
-                    ChannelInnerItem::Stats(item) => {
-                        if !item.errors.is_empty() {
-                            self.show_message(item.errors.join("\n\n"), MsgType::Warn);
-                        } else {
-                            self.stats = item;
+                    economy::record_history(self, &item);
+                }
+                Ok(ResponseItem::Stats(item)) => {
+                    self.is_network_down = item.is_offline;
+                    if !item.errors.is_empty() {
+                        self.show_message(item.errors.join("\n\n"), MsgType::Warn);
+
+                        // merged with the stored history even when another upstream failed
+                        if !item.crypto.greed_fear.data.is_empty() {
+                            self.stats.crypto.greed_fear = item.crypto.greed_fear;
                         }
-                        self.is_fetching_stats = false;
+                    } else {
+                        self.stats = item;
                     }
-                },
+                }
             }
-        };
+        }
+    }
+
+    pub fn is_fetching(&self, kind: RequestKind) -> bool {
+        self.pending_requests.contains_key(&kind)
+    }
+
+    pub fn cancel_fetch(&mut self, kind: RequestKind) {
+        if let Some(handle) = self.pending_requests.remove(&kind) {
+            handle.cancel();
+        }
+        if kind == RequestKind::Convert {
+            self.converter_setting.retry();
+        }
+    }
+
+    // e.g. when offline mode is turned on, the cancelled logos are requested again once back
+    pub fn cancel_all(&mut self) {
+        for (_, handle) in self.pending_requests.drain() {
+            handle.cancel();
+        }
+        self.converter_setting.retry();
+        self.coin_icons
+            .retain(|_, icon| !matches!(icon, latest::CoinIcon::Loading));
     }
 
-    fn fetch_latest(&mut self) {
-        if self.is_fetching_latest {
+    // an identical request in flight is reused, a different one of the same kind is replaced
+    fn send_request(&mut self, req: Request) {
+        if self.conf.net.is_offline {
             return;
         }
-        self.is_fetching_latest = true;
-
-        let tx = self.tx.clone();
-        let cache_dir = self.conf.cache_dir.clone();
-        let api_key = self.cmc_pro_api_key.clone();
-
-        std::thread::spawn(move || {
-            match latest::fetch(&api_key, cache_dir.join("latest.json").as_path()) {
-                Err(e) => {
-                    _ = tx
-                        .unwrap()
-                        .try_send(ChannelItem::ErrMsg((CurrentPanel::Latest, e.to_string())));
-                }
-                Ok(v) => {
-                    _ = tx
-                        .unwrap()
-                        .try_send(ChannelItem::Item(ChannelInnerItem::Latest(v)));
-                }
-            }
+
+        let kind = req.kind();
+        if matches!(self.pending_requests.get(&kind), Some(h) if h.req == req) {
+            return;
+        }
+
+        self.cancel_fetch(kind);
+        let handle = self.service.as_ref().unwrap().send(req);
+        self.pending_requests.insert(kind, handle);
+    }
+
+    pub fn fetch_latest(&mut self) {
+        self.send_request(Request::Latest {
+            api_base: self.conf.api.cmc.clone(),
+            api_key: self.cmc_pro_api_key.clone(),
+            currency: self.conf.latest.currency.clone(),
+            limit: self.conf.latest.limit,
+            save_path: self.conf.cache_dir.join("latest.json"),
+        });
+    }
+
+    pub fn icon_fetch_count(&self) -> usize {
+        self.pending_requests
+            .keys()
+            .filter(|kind| matches!(kind, RequestKind::Icon(_)))
+            .count()
+    }
+
+    pub fn fetch_icon(&mut self, coin_id: u64) {
+        self.send_request(Request::Icon {
+            api_base: self.conf.api.cmc_logo.clone(),
+            dir: self.conf.icons_dir.clone(),
+            coin_id,
         });
     }
 
     fn fetch_stats(&mut self) {
-        if self.is_fetching_stats {
-            return;
-        }
-        self.is_fetching_stats = true;
-
-        let tx = self.tx.clone();
-        let cache_dir = self.conf.cache_dir.clone();
-        let api_key = String::default();
-
-        std::thread::spawn(move || {
-            match stats::fetch(&api_key, cache_dir.join("stats.json").as_path()) {
-                Err(e) => {
-                    _ = tx
-                        .unwrap()
-                        .try_send(ChannelItem::ErrMsg((CurrentPanel::Stats, e.to_string())));
-                }
-                Ok(v) => {
-                    _ = tx
-                        .unwrap()
-                        .try_send(ChannelItem::Item(ChannelInnerItem::Stats(v)));
-                }
-            }
+        self.send_request(Request::Stats {
+            api_base: self.conf.api.apisvr.clone(),
+            greed_fear_base: self.conf.api.greed_fear.clone(),
+            greed_fear_limit: self.conf.stats.greed_fear_limit,
+            save_path: self.conf.cache_dir.join("stats.json"),
+            greed_fear_path: self.conf.cache_dir.join("greed_fear.json"),
+        });
+
+        self.send_request(Request::Global {
+            api_base: self.conf.api.cmc.clone(),
+            api_key: self.cmc_pro_api_key.clone(),
+            save_path: self.conf.cache_dir.join("global.json"),
+        });
+
+        self.fetch_economy();
+        self.fetch_fees();
+    }
+
+    pub fn fetch_fees(&mut self) {
+        self.send_request(Request::Fees {
+            endpoints: self.conf.fees.endpoints.clone(),
+            save_path: self.conf.cache_dir.join("fees.json"),
+        });
+    }
+
+    pub fn fetch_economy(&mut self) {
+        self.send_request(Request::Economy {
+            source: self.conf.economy.source,
+            api_base: match self.conf.economy.source {
+                EconomySource::Apisvr => self.conf.api.apisvr.clone(),
+                EconomySource::Yahoo => self.conf.api.yahoo.clone(),
+            },
+            instruments: self.conf.economy.instruments.clone(),
+            save_path: self.conf.cache_dir.join("economy.json"),
+        });
+    }
+
+    pub fn fetch_rates(&mut self, from: String, targets: Vec<String>) {
+        self.send_request(Request::Convert {
+            api_base: self.conf.api.cmc.clone(),
+            api_key: self.cmc_pro_api_key.clone(),
+            from,
+            targets,
+            save_path: self.conf.cache_dir.join("rates.json"),
         });
     }
 
@@ -435,16 +727,16 @@ impl App {
 
         let frame = Frame::none()
             .fill(match self.msg_spec.msg_type {
-                MsgType::Success => theme::SUCCESS_COLOR,
-                MsgType::Warn => theme::WARN_COLOR,
-                MsgType::Danger => theme::DANGER_COLOR,
-                _ => theme::INFO_COLOR,
+                MsgType::Success => self.theme.success_color,
+                MsgType::Warn => self.theme.warn_color,
+                MsgType::Danger => self.theme.danger_color,
+                _ => self.theme.info_color,
             })
             .rounding(0.0)
             .inner_margin(theme::PADDING)
             .stroke(Stroke {
                 width: 1.0,
-                color: Color32::BLACK,
+                color: self.theme.text_color,
             });
 
         Window::new("popup-message")
@@ -461,15 +753,253 @@ impl App {
             });
     }
 
-    fn show_message(&mut self, msg: String, msg_type: MsgType) {
+    pub fn show_message(&mut self, msg: String, msg_type: MsgType) {
         self.msg_spec.msg = msg;
         self.msg_spec.msg_type = msg_type;
         self.msg_spec.timestamp = util::timestamp();
     }
 }
 
-#[allow(unused)]
+#[allow(dead_code)]
 pub fn is_mobile(ctx: &egui::Context) -> bool {
     let screen_size = ctx.screen_rect().size();
     screen_size.x < 550.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use picon_core::{
+        config::FeeEndpoint,
+        fees::Chain,
+        test_support::{self, MockServer},
+    };
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    fn app_with(server: &MockServer, dir: &Path) -> App {
+        let mut app = App::new();
+        app.conf.api.cmc = server.cmc_url();
+        app.conf.api.apisvr = server.apisvr_url();
+        app.conf.api.cmc_logo = format!("{}/icons", server.cmc_url());
+        app.conf.api.greed_fear = server.cmc_url();
+        app.conf.fees.endpoints = [
+            (Chain::Ethereum, test_support::RPC_EVM_PATH),
+            (Chain::Solana, test_support::RPC_SOLANA_PATH),
+        ]
+        .into_iter()
+        .map(|(chain, path)| FeeEndpoint {
+            chain,
+            url: format!("{}{path}", server.cmc_url()),
+        })
+        .collect();
+        app.conf.cache_dir = dir.to_path_buf();
+        app.conf.icons_dir = app.conf.cache_dir.join("icons");
+        app
+    }
+
+    // drains the bus until no request of `kind` is in flight
+    fn wait_for(app: &mut App, kind: RequestKind) {
+        let start = Instant::now();
+        while app.is_fetching(kind) {
+            assert!(start.elapsed() < Duration::from_secs(10), "timeout");
+            std::thread::sleep(Duration::from_millis(10));
+            app.update_data();
+        }
+    }
+
+    #[test]
+    fn latest_response_updates_app() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        app.fetch_latest();
+        assert!(app.is_fetching(RequestKind::Latest));
+        wait_for(&mut app, RequestKind::Latest);
+
+        assert_eq!(app.latest.data.len(), 5);
+        assert!(app.latest.addition_info.timestamp > 0);
+        assert!(!app.is_network_down);
+        assert!(app.conf.cache_dir.join("latest.json").exists());
+        assert_eq!(app.history.coins.len(), 5);
+        assert!(app.conf.cache_dir.join("history-usd.json").exists());
+    }
+
+    #[test]
+    fn stats_response_updates_app() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        app.fetch_stats();
+        wait_for(&mut app, RequestKind::Stats);
+
+        assert_eq!(app.stats.crypto.greed_fear.data.len(), 10);
+        assert!(app.msg_spec.msg.is_empty());
+
+        wait_for(&mut app, RequestKind::Global);
+        assert_eq!(app.global.eth_dominance, 17.6244);
+        assert_eq!(app.global_history.points.len(), 1);
+        assert!(app.conf.cache_dir.join("global-history.json").exists());
+
+        wait_for(&mut app, RequestKind::Economy);
+        assert_eq!(app.economy.quotes.len(), 7);
+        assert_eq!(app.economy_history.instruments.len(), 7);
+        assert!(app.conf.cache_dir.join("economy-history.json").exists());
+
+        wait_for(&mut app, RequestKind::Fees);
+        assert_eq!(app.fees.items.len(), 2);
+        assert!(app.msg_spec.msg.is_empty());
+        assert!(app.conf.cache_dir.join("fees.json").exists());
+    }
+
+    #[test]
+    fn convert_response_updates_rates() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        app.fetch_rates("BTC".to_string(), vec!["CNY".to_string()]);
+        wait_for(&mut app, RequestKind::Convert);
+
+        assert_eq!(app.rates.get("BTC", "CNY").unwrap().rate, 445661.8451603614);
+        assert!(app.msg_spec.msg.is_empty());
+        assert!(app.conf.cache_dir.join("rates.json").exists());
+    }
+
+    #[test]
+    fn failed_conversion_is_requested_again() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+        app.conf.converter.from = "BTC".to_string();
+        app.conf.converter.targets = vec!["CNY".to_string()];
+
+        // nothing is sent while offline, so nothing is remembered as requested
+        app.conf.net.is_offline = true;
+        converter::fetch_missing(&mut app);
+        app.conf.net.is_offline = false;
+
+        app.conf.api.cmc = test_support::unreachable_url();
+        converter::fetch_missing(&mut app);
+        assert!(app.is_fetching(RequestKind::Convert));
+        wait_for(&mut app, RequestKind::Convert);
+
+        app.conf.api.cmc = server.cmc_url();
+        converter::fetch_missing(&mut app);
+        assert!(app.is_fetching(RequestKind::Convert));
+        wait_for(&mut app, RequestKind::Convert);
+        assert!(app.rates.get("BTC", "CNY").is_some());
+    }
+
+    #[test]
+    fn rate_limit_keeps_data() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+        app.fetch_latest();
+        wait_for(&mut app, RequestKind::Latest);
+
+        server.route_cmc_rate_limit();
+        app.fetch_latest();
+        wait_for(&mut app, RequestKind::Latest);
+
+        assert_eq!(app.latest.data.len(), 5);
+        assert!(app.msg_spec.msg.contains("rate limit"));
+    }
+
+    #[test]
+    fn unreachable_upstream_goes_offline() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+        app.conf.api.cmc = test_support::unreachable_url();
+
+        app.fetch_latest();
+        wait_for(&mut app, RequestKind::Latest);
+
+        assert!(app.is_network_down);
+        assert!(app.is_offline());
+        assert!(app.latest.data.is_empty());
+    }
+
+    #[test]
+    fn forced_offline_sends_nothing() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+        app.conf.net.is_offline = true;
+
+        app.fetch_latest();
+        assert!(!app.is_fetching(RequestKind::Latest));
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn cancelled_response_is_discarded() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        app.fetch_latest();
+        app.cancel_fetch(RequestKind::Latest);
+        assert!(!app.is_fetching(RequestKind::Latest));
+
+        std::thread::sleep(Duration::from_millis(200));
+        app.update_data();
+        assert!(app.latest.data.is_empty());
+    }
+
+    #[test]
+    fn cancel_all_drops_every_request() {
+        let server = MockServer::recorded();
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        app.fetch_latest();
+        app.fetch_stats();
+        app.coin_icons.insert(1, latest::CoinIcon::Loading);
+        app.fetch_icon(1);
+        assert!(app.is_fetching(RequestKind::Fees));
+
+        app.cancel_all();
+        assert!(app.pending_requests.is_empty());
+        assert!(app.coin_icons.is_empty());
+
+        std::thread::sleep(Duration::from_millis(200));
+        app.update_data();
+        assert!(app.latest.data.is_empty());
+    }
+
+    #[test]
+    fn icon_responses_update_cache() {
+        let server = MockServer::recorded();
+        server.route("/icons/1.png", 200, "png");
+        let dir = test_support::temp_dir();
+        let mut app = app_with(&server, &dir);
+
+        for coin_id in [1, 2] {
+            app.coin_icons.insert(coin_id, latest::CoinIcon::Loading);
+            app.fetch_icon(coin_id);
+            wait_for(&mut app, RequestKind::Icon(coin_id));
+        }
+
+        assert!(!app.coin_icons.contains_key(&1));
+        assert!(app.conf.icons_dir.join("1.png").exists());
+        assert!(matches!(
+            app.coin_icons.get(&2),
+            Some(latest::CoinIcon::Missing)
+        ));
+        assert!(app.msg_spec.msg.is_empty());
+
+        // an unreachable upstream is retried later rather than marked missing
+        app.conf.api.cmc_logo = test_support::unreachable_url();
+        app.fetch_icon(3);
+        wait_for(&mut app, RequestKind::Icon(3));
+        assert!(matches!(
+            app.coin_icons.get(&3),
+            Some(latest::CoinIcon::Failed(_))
+        ));
+    }
+}
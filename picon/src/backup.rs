@@ -0,0 +1,122 @@
+use super::{app::App, app::MsgType, latest, theme, tr::tr};
+use egui::{Button, FontId, RichText, TextEdit, Ui};
+use picon_core::backup::{Backup, RestoreMode};
+use std::path::Path;
+
+#[derive(Default, Debug, Clone)]
+pub struct Setting {
+    // the archive to import, e.g. one copied from the other device
+    pub path: String,
+    pub mode: RestoreMode,
+}
+
+// the archive reads the markers from `marker_symbols.json`, a toggle still pending is written first
+fn export(app: &mut App) {
+    let locale = app.conf.ui.locale;
+    latest::save_markers(app);
+    match Backup::collect(&app.conf).write(&app.conf.export_dir()) {
+        Ok(path) => {
+            app.backup_setting.path = path.display().to_string();
+            app.show_message(
+                format!("{}\n{}", tr(locale, "exported_to"), path.display()),
+                MsgType::Success,
+            );
+        }
+        Err(e) => app.show_message(format!("{e:?}"), MsgType::Warn),
+    }
+}
+
+fn import(app: &mut App) {
+    let locale = app.conf.ui.locale;
+    let path = app.backup_setting.path.trim().to_string();
+    // a pending toggle written after the restore would replace the restored markers
+    latest::save_markers(app);
+    let result = Backup::read(Path::new(&path))
+        .and_then(|backup| backup.restore(&mut app.conf, app.backup_setting.mode));
+
+    match result {
+        Ok(_) => {
+            // the markers and holdings are read again, the settings apply on the next frame
+            latest::init(app);
+            app.fetch_latest();
+            app.show_message(tr(locale, "backup_restored"), MsgType::Success);
+        }
+        Err(e) => app.show_message(format!("{e:?}"), MsgType::Warn),
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "backup"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        if ui.button(tr(locale, "export_backup")).clicked() {
+            export(app);
+        }
+
+        ui.add_space(theme::SPACING * 2.);
+
+        ui.add(
+            TextEdit::singleline(&mut app.backup_setting.path).hint_text(tr(locale, "backup_path")),
+        );
+
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut app.backup_setting.mode,
+                RestoreMode::Merge,
+                tr(locale, "merge"),
+            );
+            ui.radio_value(
+                &mut app.backup_setting.mode,
+                RestoreMode::Replace,
+                tr(locale, "replace"),
+            );
+        });
+
+        if ui
+            .add_enabled(
+                !app.backup_setting.path.trim().is_empty(),
+                Button::new(tr(locale, "import_backup")),
+            )
+            .clicked()
+        {
+            import(app);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use picon_core::{cache, test_support};
+
+    #[test]
+    fn pending_markers_are_kept_by_a_restore() {
+        let dir = test_support::temp_dir();
+        let mut app = App::new();
+        app.conf.cache_dir = dir.to_path_buf();
+        app.conf.config_path = dir.join("picoin.conf");
+        app.conf.export.dir = dir.display().to_string();
+        app.conf.net.is_offline = true;
+
+        latest::update_marker_symbols(&mut app, "BTC");
+        export(&mut app);
+
+        // toggled right before the import, still waiting to be written
+        latest::update_marker_symbols(&mut app, "BTC");
+        latest::update_marker_symbols(&mut app, "ETH");
+        app.backup_setting.mode = RestoreMode::Merge;
+        import(&mut app);
+        latest::save_markers(&mut app);
+
+        let path = dir.join("marker_symbols.json");
+        let markers = cache::load::<Vec<String>>(&path).unwrap();
+        assert_eq!(markers, ["BTC", "ETH"]);
+    }
+}
@@ -0,0 +1,308 @@
+use super::{app::App, theme, tr::tr};
+use egui::{
+    pos2, vec2, Align, Align2, FontId, Layout, Rect, RichText, Rounding, Sense, Shape, Stroke, Ui,
+};
+use picon_core::{
+    breadth::{self, Period, Summary, BUCKET_BOUNDS},
+    config::Locale,
+    format,
+    latest::LatestDataItem,
+};
+
+const TOP_COUNT: usize = 5;
+const CHART_HEIGHT: f32 = 80.;
+
+#[derive(Default, Debug, Clone)]
+pub struct Setting {
+    pub period: Period,
+
+    // the advance/decline ratios, recomputed when the period or the recorded listings change
+    advance_decline: Vec<(i64, f64)>,
+    advance_decline_key: Option<(Period, i64)>,
+}
+
+fn period_name(locale: Locale, period: Period) -> String {
+    match period {
+        Period::H1 => tr(locale, "1h"),
+        Period::H24 => tr(locale, "24h"),
+        Period::D7 => tr(locale, "7d"),
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let period = app.breadth_setting.period;
+    let summary = Summary::new(&app.latest, period);
+
+    ui.vertical(|ui| {
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(tr(locale, "breadth"))
+                    .color(app.theme.text_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+            );
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            for item in Period::ALL {
+                let text = RichText::new(period_name(locale, item))
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE));
+                if ui.selectable_label(item == period, text).clicked() {
+                    app.breadth_setting.period = item;
+                }
+            }
+        });
+
+        ui.add_space(theme::SPACING);
+        summary_ui(app, ui, &summary);
+
+        ui.add_space(theme::SPACING);
+        histogram(app, ui, &summary);
+
+        ui.add_space(theme::SPACING * 2.);
+        advance_decline_ui(app, ui);
+
+        ui.add_space(theme::SPACING * 2.);
+        movers_ui(app, ui);
+    });
+}
+
+fn summary_ui(app: &App, ui: &mut Ui, summary: &Summary) {
+    let locale = app.conf.ui.locale;
+    let color = |v: f64| {
+        if v >= 0. {
+            app.theme.up_color
+        } else {
+            app.theme.down_color
+        }
+    };
+
+    let items = [
+        (
+            tr(locale, "advancers"),
+            summary.advancers.to_string(),
+            app.theme.up_color,
+        ),
+        (
+            tr(locale, "decliners"),
+            summary.decliners.to_string(),
+            app.theme.down_color,
+        ),
+        (
+            tr(locale, "advance_decline"),
+            format::number(locale, summary.ratio(), 2),
+            color(summary.ratio().ln()),
+        ),
+        (
+            tr(locale, "median"),
+            format::percent(locale, summary.median),
+            color(summary.median),
+        ),
+        (
+            tr(locale, "mean"),
+            format::percent(locale, summary.mean),
+            color(summary.mean),
+        ),
+    ];
+
+    ui.columns(items.len(), |columns| {
+        for (ui, (name, value, color)) in columns.iter_mut().zip(items) {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    RichText::new(name)
+                        .color(app.theme.light_color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8)),
+                );
+                ui.label(
+                    RichText::new(value)
+                        .color(color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                );
+            });
+        }
+    });
+}
+
+// the labels of the buckets, e.g. "<-10%", "-10~-5%", ">10%"
+fn bucket_label(i: usize) -> String {
+    match i {
+        0 => format!("<{}%", BUCKET_BOUNDS[0]),
+        i if i == BUCKET_BOUNDS.len() => format!(">{}%", BUCKET_BOUNDS[i - 1]),
+        i => format!("{}~{}%", BUCKET_BOUNDS[i - 1], BUCKET_BOUNDS[i]),
+    }
+}
+
+fn histogram(app: &App, ui: &mut Ui, summary: &Summary) {
+    let label_height = theme::DEFAULT_FONT_SIZE;
+    let (rect, _) = ui.allocate_exact_size(
+        vec2(ui.available_width(), CHART_HEIGHT + label_height),
+        Sense::hover(),
+    );
+    let max = summary.buckets.iter().copied().max().unwrap_or_default();
+    if max == 0 || !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let painter = ui.painter_at(rect);
+    let width = rect.width() / summary.buckets.len() as f32;
+
+    for (i, &count) in summary.buckets.iter().enumerate() {
+        let left = rect.left() + i as f32 * width;
+        let height = CHART_HEIGHT * count as f32 / max as f32;
+        let bar = Rect::from_min_max(
+            pos2(left + 2., rect.top() + CHART_HEIGHT - height),
+            pos2(left + width - 2., rect.top() + CHART_HEIGHT),
+        );
+
+        // the buckets left of zero are declines
+        let color = if i < BUCKET_BOUNDS.iter().position(|&v| v >= 0.).unwrap_or(0) {
+            app.theme.down_color
+        } else {
+            app.theme.up_color
+        };
+        painter.rect_filled(bar, Rounding::none(), color);
+
+        if count > 0 {
+            painter.text(
+                pos2(bar.center().x, bar.top()),
+                Align2::CENTER_BOTTOM,
+                count.to_string(),
+                FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.7),
+                app.theme.text_color,
+            );
+        }
+
+        painter.text(
+            pos2(left + width / 2., rect.bottom()),
+            Align2::CENTER_BOTTOM,
+            bucket_label(i),
+            FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.7),
+            app.theme.light_color,
+        );
+    }
+}
+
+fn advance_decline_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let period = app.breadth_setting.period;
+    let timestamp = app.latest.addition_info.timestamp;
+
+    if app.breadth_setting.advance_decline_key != Some((period, timestamp)) {
+        app.breadth_setting.advance_decline = breadth::advance_decline(&app.history, period);
+        app.breadth_setting.advance_decline_key = Some((period, timestamp));
+    }
+
+    // the ratio is drawn on a log scale, so 2:1 and 1:2 are equally far from the baseline
+    let points = app
+        .breadth_setting
+        .advance_decline
+        .iter()
+        .filter(|p| p.1.is_finite() && p.1 > 0.)
+        .map(|p| (p.0, p.1.ln()))
+        .collect::<Vec<_>>();
+
+    ui.label(
+        RichText::new(format!(
+            "{} ({})",
+            tr(locale, "advance_decline"),
+            period_name(locale, period)
+        ))
+        .color(app.theme.text_color)
+        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+    );
+
+    let (rect, resp) =
+        ui.allocate_exact_size(vec2(ui.available_width(), CHART_HEIGHT), Sense::hover());
+    if points.len() < 2 || !ui.is_rect_visible(rect) {
+        ui.painter().text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            tr(locale, "no_history"),
+            FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8),
+            app.theme.light_color,
+        );
+        return;
+    }
+
+    let (first, last) = (points[0].0, points[points.len() - 1].0);
+    let extent = points
+        .iter()
+        .map(|p| p.1.abs())
+        .fold(f64::MIN_POSITIVE, f64::max);
+    let to_pos = |ts: i64, v: f64| {
+        pos2(
+            rect.left() + rect.width() * (ts - first) as f32 / (last - first).max(1) as f32,
+            rect.center().y - rect.height() / 2. * (v / extent) as f32,
+        )
+    };
+
+    let painter = ui.painter_at(rect);
+    painter.hline(
+        rect.x_range(),
+        rect.center().y,
+        Stroke::new(1.0, app.theme.light_color),
+    );
+
+    let line = points.iter().map(|p| to_pos(p.0, p.1)).collect();
+    let color = if points[points.len() - 1].1 >= 0. {
+        app.theme.up_color
+    } else {
+        app.theme.down_color
+    };
+    painter.add(Shape::line(line, Stroke::new(1.5, color)));
+
+    let hover = format!(
+        "{} - {}",
+        format::datetime(locale, first),
+        format::datetime(locale, last)
+    );
+    resp.on_hover_text(hover);
+}
+
+fn movers_ui(app: &App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let period = app.breadth_setting.period;
+    let (gainers, losers) = breadth::movers(&app.latest, period, TOP_COUNT);
+
+    ui.columns(2, |columns| {
+        movers_column(app, &mut columns[0], tr(locale, "gainers"), &gainers);
+        movers_column(app, &mut columns[1], tr(locale, "losers"), &losers);
+    });
+}
+
+fn movers_column(app: &App, ui: &mut Ui, title: String, items: &[&LatestDataItem]) {
+    let locale = app.conf.ui.locale;
+    let period = app.breadth_setting.period;
+
+    ui.label(
+        RichText::new(title)
+            .color(app.theme.text_color)
+            .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+    );
+
+    for item in items {
+        let change = period.change(item);
+        let color = if change >= 0. {
+            app.theme.up_color
+        } else {
+            app.theme.down_color
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(&item.symbol)
+                    .color(app.theme.text_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+            );
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                ui.label(
+                    RichText::new(format::percent(locale, change))
+                        .color(color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                );
+            });
+        });
+    }
+}
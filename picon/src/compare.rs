@@ -0,0 +1,309 @@
+use super::{app::App, latest, theme, tr::tr};
+use egui::{
+    pos2, vec2, Align2, Button, Color32, FontId, Grid, RichText, ScrollArea, Sense, Shape, Stroke,
+    Ui,
+};
+use picon_core::{
+    compare::{self, Range, MAX_COINS},
+    config::{LatestColumn, Locale},
+    format,
+    latest::LatestDataItem,
+};
+
+const CHART_HEIGHT: f32 = 200.;
+
+#[derive(Default, Debug, Clone)]
+pub struct Setting {
+    // picked in the Latest panel, in the order they were picked
+    pub coin_ids: Vec<u64>,
+    pub range: Range,
+}
+
+// a coin is picked by a click on its rank, at most `MAX_COINS`
+pub fn toggle(app: &mut App, coin_id: u64) {
+    let ids = &mut app.compare_setting.coin_ids;
+    if let Some(i) = ids.iter().position(|v| *v == coin_id) {
+        ids.remove(i);
+    } else if ids.len() < MAX_COINS {
+        ids.push(coin_id);
+    }
+}
+
+pub fn is_picked(app: &App, coin_id: u64) -> bool {
+    app.compare_setting.coin_ids.contains(&coin_id)
+}
+
+fn range_name(locale: Locale, range: Range) -> String {
+    match range {
+        Range::D1 => tr(locale, "24h"),
+        Range::D7 => tr(locale, "7d"),
+        Range::D30 => tr(locale, "30d"),
+    }
+}
+
+fn coin_color(app: &App, i: usize) -> Color32 {
+    let theme = &app.theme;
+    [
+        theme.brand_color,
+        theme.warn_color,
+        theme.success_color,
+        theme.danger_color,
+        theme.info_color,
+    ][i % MAX_COINS]
+}
+
+// the picked coins which are still in the listings
+fn coins(app: &App) -> Vec<LatestDataItem> {
+    app.compare_setting
+        .coin_ids
+        .iter()
+        .filter_map(|id| app.latest.data.iter().find(|v| v.id == *id))
+        .cloned()
+        .collect()
+}
+
+// the local snapshots of the range, ending with the price of the listings
+fn prices(app: &App, coin: &LatestDataItem) -> Vec<(i64, f64)> {
+    let now = app.latest.addition_info.timestamp;
+    let mut points = app
+        .history
+        .series(coin.id, now - app.compare_setting.range.secs());
+
+    if !points.iter().any(|p| p.0 >= now) && coin.quote.value.price > 0. {
+        points.push((now, coin.quote.value.price));
+    }
+    points
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+
+    if ui
+        .add(
+            Button::image_and_text(
+                app.back_icon.clone().unwrap().id(),
+                theme::BACK_ICON_SIZE,
+                RichText::new(tr(locale, "compare"))
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+            )
+            .frame(false),
+        )
+        .clicked()
+    {
+        app.current_panel = app.prev_panel;
+    }
+
+    ui.add_space(theme::SPACING * 2.);
+
+    let coins = coins(app);
+    if coins.len() < compare::MIN_COINS {
+        ui.vertical_centered(|ui| {
+            ui.label(RichText::new(tr(locale, "compare_hint")).color(app.theme.light_color));
+        });
+        return;
+    }
+
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            metrics_ui(app, ui, &coins);
+            ui.add_space(theme::SPACING * 4.);
+
+            ui.horizontal(|ui| {
+                for range in Range::ALL {
+                    if ui
+                        .selectable_label(
+                            app.compare_setting.range == range,
+                            range_name(locale, range),
+                        )
+                        .clicked()
+                    {
+                        app.compare_setting.range = range;
+                    }
+                }
+            });
+
+            let series = coins
+                .iter()
+                .map(|coin| prices(app, coin))
+                .collect::<Vec<_>>();
+
+            chart_ui(app, ui, &coins, &series);
+            ui.add_space(theme::SPACING * 4.);
+            correlation_ui(app, ui, &coins, &series);
+        });
+}
+
+fn metric(coin: &LatestDataItem, column: LatestColumn) -> f64 {
+    let value = &coin.quote.value;
+    match column {
+        LatestColumn::H24 => value.percent_change_24h,
+        LatestColumn::D7 => value.percent_change_7d,
+        LatestColumn::D30 => value.percent_change_30d,
+        LatestColumn::MarketCap => value.market_cap,
+        LatestColumn::Volume24h => value.volume_24h,
+        _ => value.price,
+    }
+}
+
+// one column per coin
+fn metrics_ui(app: &App, ui: &mut Ui, coins: &[LatestDataItem]) {
+    let locale = app.conf.ui.locale;
+    let percent = |ui: &mut Ui, value: f64| {
+        let color = if value >= 0. {
+            app.theme.up_color
+        } else {
+            app.theme.down_color
+        };
+        ui.label(RichText::new(format::percent(locale, value)).color(color));
+    };
+
+    Grid::new("compare-metrics")
+        .striped(true)
+        .spacing(vec2(theme::SPACING * 4., theme::SPACING * 2.))
+        .show(ui, |ui| {
+            ui.label("");
+            for (i, coin) in coins.iter().enumerate() {
+                ui.label(
+                    RichText::new(&coin.symbol)
+                        .color(coin_color(app, i))
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+                );
+            }
+            ui.end_row();
+
+            ui.label(tr(locale, "rank"));
+            for coin in coins {
+                ui.label(coin.rank.to_string());
+            }
+            ui.end_row();
+
+            ui.label(latest::column_name(locale, LatestColumn::Price));
+            for coin in coins {
+                ui.label(format::price(locale, coin.quote.value.price));
+            }
+            ui.end_row();
+
+            for column in [LatestColumn::H24, LatestColumn::D7, LatestColumn::D30] {
+                ui.label(latest::column_name(locale, column));
+                for coin in coins {
+                    percent(ui, metric(coin, column));
+                }
+                ui.end_row();
+            }
+
+            for column in [LatestColumn::MarketCap, LatestColumn::Volume24h] {
+                ui.label(latest::column_name(locale, column));
+                for coin in coins {
+                    ui.label(format::money(
+                        locale,
+                        &coin.quote.currency,
+                        metric(coin, column),
+                        true,
+                    ));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+// every coin rebased to 100 at the start of the range
+fn chart_ui(app: &App, ui: &mut Ui, coins: &[LatestDataItem], series: &[Vec<(i64, f64)>]) {
+    let locale = app.conf.ui.locale;
+    let lines = series
+        .iter()
+        .map(|v| compare::rebase(v))
+        .collect::<Vec<_>>();
+
+    let (rect, resp) =
+        ui.allocate_exact_size(vec2(ui.available_width(), CHART_HEIGHT), Sense::hover());
+    if !lines.iter().any(|v| v.len() >= 2) {
+        ui.painter().text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            tr(locale, "no_history"),
+            FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8),
+            app.theme.light_color,
+        );
+        return;
+    }
+
+    let points = lines.iter().flatten();
+    let (first, last) = points
+        .clone()
+        .fold((i64::MAX, i64::MIN), |(a, b), p| (a.min(p.0), b.max(p.0)));
+    let (min, max) = points.fold((100_f64, 100_f64), |(a, b), p| (a.min(p.1), b.max(p.1)));
+    let span_y = if max > min { max - min } else { 1. };
+
+    let to_pos = |ts: i64, v: f64| {
+        pos2(
+            rect.left() + rect.width() * (ts - first) as f32 / (last - first).max(1) as f32,
+            rect.bottom() - rect.height() * ((v - min) / span_y) as f32,
+        )
+    };
+
+    let painter = ui.painter_at(rect);
+    let base = to_pos(first, 100.).y;
+    painter.hline(
+        rect.x_range(),
+        base,
+        Stroke::new(0.5, app.theme.light_color),
+    );
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.iter().map(|p| to_pos(p.0, p.1)).collect();
+        painter.add(Shape::line(line, Stroke::new(1.5, coin_color(app, i))));
+    }
+
+    let Some(pointer) = resp.hover_pos() else {
+        return;
+    };
+
+    // the value of every coin at the hovered time
+    let ts = first + ((pointer.x - rect.left()) / rect.width() * (last - first) as f32) as i64;
+    let mut text = format::datetime(locale, ts);
+    for (coin, line) in coins.iter().zip(lines.iter()) {
+        if let Some(p) = line.iter().min_by_key(|p| (p.0 - ts).abs()) {
+            text.push_str(&format!(
+                "\n{}: {}",
+                coin.symbol,
+                format::number(locale, p.1, 2)
+            ));
+        }
+    }
+    resp.on_hover_text(text);
+}
+
+fn correlation_ui(app: &App, ui: &mut Ui, coins: &[LatestDataItem], series: &[Vec<(i64, f64)>]) {
+    let locale = app.conf.ui.locale;
+
+    ui.label(
+        RichText::new(tr(locale, "correlation"))
+            .color(app.theme.title_color)
+            .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+    );
+    ui.separator();
+
+    Grid::new("compare-correlation")
+        .spacing(vec2(theme::SPACING * 4., theme::SPACING * 2.))
+        .show(ui, |ui| {
+            ui.label("");
+            for (i, coin) in coins.iter().enumerate() {
+                ui.label(RichText::new(&coin.symbol).color(coin_color(app, i)));
+            }
+            ui.end_row();
+
+            for (i, a) in coins.iter().enumerate() {
+                ui.label(RichText::new(&a.symbol).color(coin_color(app, i)));
+                for j in 0..coins.len() {
+                    let text = match compare::correlation(&series[i], &series[j]) {
+                        _ if i == j => "1.00".to_string(),
+                        Some(v) => format::number(locale, v, 2),
+                        None => "-".to_string(),
+                    };
+                    ui.label(text);
+                }
+                ui.end_row();
+            }
+        });
+}
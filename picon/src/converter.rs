@@ -0,0 +1,282 @@
+use super::{app::App, setting, theme, tr::tr};
+use egui::{Button, ComboBox, FontId, RichText, ScrollArea, TextEdit, Ui};
+use egui_extras::{Size, StripBuilder};
+use picon_core::{
+    cache,
+    convert::{self, Rate, Rates},
+    format,
+    service::RequestKind,
+    util,
+};
+
+#[derive(Clone, Debug)]
+pub struct Setting {
+    pub amount: String,
+
+    // the targets of the last price-conversion request, not requested again on every frame
+    requested: Option<Vec<String>>,
+}
+
+impl Setting {
+    // the missing rates are requested again, e.g. after the last request failed or was cancelled
+    pub fn retry(&mut self) {
+        self.requested = None;
+    }
+}
+
+impl Default for Setting {
+    fn default() -> Self {
+        Self {
+            amount: "1".to_string(),
+            requested: None,
+        }
+    }
+}
+
+pub fn init(app: &mut App) {
+    match cache::load::<Rates>(&app.conf.cache_dir.join("rates.json")) {
+        Ok(v) => app.rates = v,
+        Err(e) => log::debug!("{e:?}"),
+    }
+}
+
+// the quote currencies and then the listed coins, in rank order
+fn symbols(app: &App) -> Vec<String> {
+    let mut symbols = setting::CURRENCIES
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+
+    for item in app.latest.data.iter() {
+        if !symbols.contains(&item.symbol) {
+            symbols.push(item.symbol.clone());
+        }
+    }
+    symbols
+}
+
+// the price-conversion api converts from coins only, so the currencies which are not
+// in the listings are fetched as BTC pairs and the other pairs are crossed through it
+const PIVOT: &str = "BTC";
+
+// the listings first, they are newer than the fetched rates most of the time
+fn direct_rate(app: &App, from: &str, to: &str) -> Option<Rate> {
+    convert::rate_from_latest(&app.latest, from, to).or_else(|| app.rates.get(from, to))
+}
+
+fn rate(app: &App, from: &str, to: &str) -> Option<Rate> {
+    if from == to {
+        return Some(Rate {
+            from: from.to_string(),
+            to: to.to_string(),
+            rate: 1.,
+            timestamp: util::timestamp(),
+        });
+    }
+
+    direct_rate(app, from, to).or_else(|| {
+        let (a, b) = (direct_rate(app, from, PIVOT)?, direct_rate(app, PIVOT, to)?);
+        Some(Rate {
+            from: from.to_string(),
+            to: to.to_string(),
+            rate: a.rate * b.rate,
+            timestamp: a.timestamp.min(b.timestamp),
+        })
+    })
+}
+
+// the amount can be typed with grouping separators, e.g. "1,000.5"
+fn parse_amount(text: &str) -> Option<f64> {
+    text.trim()
+        .replace([',', '_', ' '], "")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+}
+
+fn symbol_combo(ui: &mut Ui, id: impl std::hash::Hash, symbols: &[String], value: &mut String) {
+    ComboBox::from_id_source(id)
+        .selected_text(value.as_str())
+        .width(90.)
+        .show_ui(ui, |ui| {
+            ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                for item in symbols {
+                    ui.selectable_value(value, item.clone(), item);
+                }
+            });
+        });
+}
+
+// the symbols without a BTC rate are requested from the price-conversion api
+pub fn fetch_missing(app: &mut App) {
+    let conf = &app.conf.converter;
+    let mut missing = vec![];
+    for symbol in std::iter::once(&conf.from).chain(conf.targets.iter()) {
+        if symbol != PIVOT && direct_rate(app, PIVOT, symbol).is_none() && !missing.contains(symbol)
+        {
+            missing.push(symbol.clone());
+        }
+    }
+
+    if missing.is_empty()
+        || app.conf.net.is_offline
+        || app.converter_setting.requested.as_ref() == Some(&missing)
+    {
+        return;
+    }
+
+    app.converter_setting.requested = Some(missing.clone());
+    app.fetch_rates(PIVOT.to_string(), missing);
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let symbols = symbols(app);
+    let mut is_changed = false;
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut app.converter_setting.amount)
+                    .desired_width(160.)
+                    .hint_text(tr(locale, "amount")),
+            );
+
+            let from = app.conf.converter.from.clone();
+            symbol_combo(ui, "converter-from", &symbols, &mut app.conf.converter.from);
+            is_changed |= from != app.conf.converter.from;
+
+            // a single row swaps its pair
+            if app.conf.converter.targets.len() == 1
+                && ui
+                    .add(Button::new("⇄").frame(false))
+                    .on_hover_text(tr(locale, "swap_pair"))
+                    .clicked()
+            {
+                std::mem::swap(
+                    &mut app.conf.converter.from,
+                    &mut app.conf.converter.targets[0],
+                );
+                is_changed = true;
+            }
+        });
+
+        ui.separator();
+
+        let amount = parse_amount(&app.converter_setting.amount);
+        let from = app.conf.converter.from.clone();
+        let is_fetching = app.is_fetching(RequestKind::Convert);
+        let mut removed = None;
+
+        for i in 0..app.conf.converter.targets.len() {
+            let to = app.conf.converter.targets[i].clone();
+            let rate = rate(app, &from, &to);
+            let color = match rate {
+                Some(ref rate) if !app.is_stale(rate.timestamp) => app.theme.text_color,
+                _ => app.theme.light_color,
+            };
+
+            ui.horizontal(|ui| {
+                StripBuilder::new(ui)
+                    .size(Size::exact(100.))
+                    .size(Size::relative(0.45))
+                    .size(Size::remainder())
+                    .size(Size::exact(20.))
+                    .horizontal(|mut strip| {
+                        strip.cell(|ui| {
+                            symbol_combo(
+                                ui,
+                                ("converter-to", i),
+                                &symbols,
+                                &mut app.conf.converter.targets[i],
+                            );
+                        });
+
+                        strip.cell(|ui| {
+                            let text = match (amount, &rate) {
+                                (Some(amount), Some(rate)) => {
+                                    format!("{} {to}", format::price(locale, amount * rate.rate))
+                                }
+                                _ if is_fetching => "…".to_string(),
+                                _ => "-".to_string(),
+                            };
+                            ui.label(
+                                RichText::new(text)
+                                    .color(color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+                            );
+                        });
+
+                        strip.cell(|ui| {
+                            if let Some(rate) = &rate {
+                                ui.label(
+                                    RichText::new(format!(
+                                        "1 {from} = {} {to} ({})",
+                                        format::price(locale, rate.rate),
+                                        util::data_age(rate.timestamp)
+                                    ))
+                                    .color(app.theme.light_color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8)),
+                                )
+                                .on_hover_text(format::datetime(locale, rate.timestamp));
+                            }
+                        });
+
+                        strip.cell(|ui| {
+                            if ui
+                                .add_enabled(
+                                    app.conf.converter.targets.len() > 1,
+                                    Button::new("✖").frame(false),
+                                )
+                                .clicked()
+                            {
+                                removed = Some(i);
+                            }
+                        });
+                    });
+            });
+
+            is_changed |= to != app.conf.converter.targets[i];
+        }
+
+        if let Some(i) = removed {
+            app.conf.converter.targets.remove(i);
+            is_changed = true;
+        }
+
+        if ui
+            .add(Button::new(format!("+ {}", tr(locale, "add_row"))).frame(false))
+            .clicked()
+        {
+            // the first quote currency which is not shown yet
+            let next = symbols
+                .iter()
+                .find(|v| **v != from && !app.conf.converter.targets.contains(v))
+                .cloned()
+                .unwrap_or_else(|| from.clone());
+            app.conf.converter.targets.push(next);
+            is_changed = true;
+        }
+    });
+
+    if is_changed {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+
+    fetch_missing(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_typed_amount() {
+        assert_eq!(parse_amount(" 1,000.5 "), Some(1000.5));
+        assert_eq!(parse_amount("2"), Some(2.));
+        assert_eq!(parse_amount(""), None);
+        assert_eq!(parse_amount("abc"), None);
+    }
+}
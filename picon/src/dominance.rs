@@ -0,0 +1,148 @@
+use super::{app::App, theme, tr::tr};
+use egui::{pos2, vec2, Align2, Color32, FontId, RichText, Sense, Shape, Stroke, Ui};
+use picon_core::{
+    cache, format,
+    global::{self, GlobalHistory, GlobalMetrics, GlobalPoint},
+};
+
+const CHART_HEIGHT: f32 = 120.;
+
+// name, line color and value of a charted series
+type Series = (String, Color32, fn(&GlobalPoint) -> f64);
+
+pub fn init(app: &mut App) {
+    match cache::load::<GlobalMetrics>(&app.conf.cache_dir.join("global.json")) {
+        Ok(v) => app.global = v,
+        Err(e) => log::debug!("{e:?}"),
+    }
+
+    match cache::load::<GlobalHistory>(&app.conf.cache_dir.join("global-history.json")) {
+        Ok(v) => app.global_history = v,
+        Err(e) => log::debug!("{e:?}"),
+    }
+}
+
+// called after every global metrics response
+pub fn record_history(app: &mut App) {
+    let altcoin_season = global::altcoin_season(&app.latest);
+    app.global_history.record(&app.global, altcoin_season);
+
+    let path = app.conf.cache_dir.join("global-history.json");
+    if let Err(e) = cache::save(&path, &app.global_history) {
+        log::warn!("{e:?}");
+    }
+}
+
+fn series(app: &App) -> [Series; 4] {
+    let locale = app.conf.ui.locale;
+    [
+        (tr(locale, "btc_dominance"), app.theme.brand_color, |p| {
+            p.btc_dominance
+        }),
+        (tr(locale, "eth_dominance"), app.theme.info_color, |p| {
+            p.eth_dominance
+        }),
+        (
+            tr(locale, "stablecoin_market_cap"),
+            app.theme.success_color,
+            |p| p.stablecoin_share,
+        ),
+        (tr(locale, "altcoin_season"), app.theme.warn_color, |p| {
+            p.altcoin_season.unwrap_or(f64::NAN)
+        }),
+    ]
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let points = &app.global_history.points;
+
+    ui.vertical(|ui| {
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(tr(locale, "dominance"))
+                    .color(app.theme.text_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+            );
+        });
+
+        ui.separator();
+
+        ui.horizontal_wrapped(|ui| {
+            for (name, color, _) in series(app) {
+                ui.label(
+                    RichText::new(format!("● {name}"))
+                        .color(color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8)),
+                );
+            }
+        });
+
+        let (rect, resp) =
+            ui.allocate_exact_size(vec2(ui.available_width(), CHART_HEIGHT), Sense::hover());
+        if points.len() < 2 || !ui.is_rect_visible(rect) {
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                tr(locale, "no_history"),
+                FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8),
+                app.theme.light_color,
+            );
+            return;
+        }
+
+        // every series is in percent, the scale is shared so the shifts compare
+        let series = series(app);
+        let max = points
+            .iter()
+            .flat_map(|p| series.iter().map(move |s| (s.2)(p)))
+            .filter(|v| v.is_finite())
+            .fold(1., f64::max);
+
+        let (first, last) = (points[0].timestamp, points[points.len() - 1].timestamp);
+        let to_pos = |ts: i64, v: f64| {
+            pos2(
+                rect.left() + rect.width() * (ts - first) as f32 / (last - first).max(1) as f32,
+                rect.bottom() - rect.height() * (v / max) as f32,
+            )
+        };
+
+        let painter = ui.painter_at(rect);
+        for (_, color, value) in series.iter() {
+            // a gap in the series, e.g. the altcoin season without listings, splits the line
+            let mut line = vec![];
+            for p in points.iter() {
+                let v = value(p);
+                if v.is_finite() {
+                    line.push(to_pos(p.timestamp, v));
+                } else if !line.is_empty() {
+                    painter.add(Shape::line(
+                        std::mem::take(&mut line),
+                        Stroke::new(1.5, *color),
+                    ));
+                }
+            }
+            painter.add(Shape::line(line, Stroke::new(1.5, *color)));
+        }
+
+        let Some(pointer) = resp.hover_pos() else {
+            return;
+        };
+
+        let Some(point) = points
+            .iter()
+            .min_by_key(|p| (to_pos(p.timestamp, 0.).x - pointer.x).abs() as i64)
+        else {
+            return;
+        };
+
+        let mut text = format::datetime(locale, point.timestamp);
+        for (name, _, value) in series.iter() {
+            text.push_str(&format!(
+                "\n{name}: {}",
+                format::percent(locale, value(point))
+            ));
+        }
+        resp.on_hover_text(text);
+    });
+}
@@ -0,0 +1,200 @@
+use super::{app::App, theme, tr::tr};
+use anyhow::Result;
+use egui::{pos2, vec2, FontId, RichText, Sense, Shape, Stroke, Ui};
+use egui_extras::{Size, StripBuilder};
+use picon_core::{
+    cache,
+    config::Locale,
+    economy::{Economy, EconomyHistory, EconomySource, InstrumentId, InstrumentKind, Quote},
+    format, util,
+};
+
+pub fn instrument_name(locale: Locale, id: InstrumentId) -> String {
+    match id {
+        InstrumentId::Sse => tr(locale, "instrument.sse"),
+        InstrumentId::Szse => tr(locale, "instrument.szse"),
+        InstrumentId::Chinext => tr(locale, "instrument.chinext"),
+        InstrumentId::Hsi => tr(locale, "instrument.hsi"),
+        InstrumentId::N225 => tr(locale, "instrument.n225"),
+        InstrumentId::Spx => tr(locale, "instrument.spx"),
+        InstrumentId::Ndx => tr(locale, "instrument.ndx"),
+        InstrumentId::Dji => tr(locale, "instrument.dji"),
+        InstrumentId::Dxy => tr(locale, "instrument.dxy"),
+        InstrumentId::UsdCnh => tr(locale, "instrument.usd_cnh"),
+        InstrumentId::EurUsd => tr(locale, "instrument.eur_usd"),
+        InstrumentId::UsdJpy => tr(locale, "instrument.usd_jpy"),
+        InstrumentId::Gold => tr(locale, "instrument.gold"),
+        InstrumentId::Wti => tr(locale, "instrument.wti"),
+        InstrumentId::Brent => tr(locale, "instrument.brent"),
+        InstrumentId::Us5y => tr(locale, "instrument.us5y"),
+        InstrumentId::Us10y => tr(locale, "instrument.us10y"),
+    }
+}
+
+pub fn kind_name(locale: Locale, kind: InstrumentKind) -> String {
+    match kind {
+        InstrumentKind::Index => tr(locale, "kind_index"),
+        InstrumentKind::Fx => tr(locale, "kind_fx"),
+        InstrumentKind::Commodity => tr(locale, "kind_commodity"),
+        InstrumentKind::Yield => tr(locale, "kind_yield"),
+    }
+}
+
+pub fn source_name(source: EconomySource) -> &'static str {
+    match source {
+        EconomySource::Apisvr => "apisvr",
+        EconomySource::Yahoo => "Yahoo Finance",
+    }
+}
+
+pub fn init(app: &mut App) {
+    match cache::load::<Economy>(&app.conf.cache_dir.join("economy.json")) {
+        Ok(v) => app.economy = v,
+        Err(e) => log::debug!("{e:?}"),
+    }
+
+    match cache::load::<EconomyHistory>(&app.conf.cache_dir.join("economy-history.json")) {
+        Ok(v) => app.economy_history = v,
+        Err(e) => log::debug!("{e:?}"),
+    }
+}
+
+// called after every economy response, with the quotes of the response
+pub fn record_history(app: &mut App, economy: &Economy) {
+    app.economy_history.record(economy);
+    if let Err(e) = save_history(app) {
+        log::warn!("{e:?}");
+    }
+}
+
+fn save_history(app: &App) -> Result<()> {
+    cache::save(
+        &app.conf.cache_dir.join("economy-history.json"),
+        &app.economy_history,
+    )
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let updated_at = app.economy.timestamp;
+    let is_stale = app.is_stale(updated_at);
+
+    ui.vertical(|ui| {
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(format!(
+                    "{}({})",
+                    tr(locale, "economy"),
+                    util::data_age(updated_at)
+                ))
+                .color(app.theme.text_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+            )
+            .on_hover_text(format::datetime(locale, updated_at));
+        });
+
+        ui.separator();
+
+        // in the configured order, the deselected instruments of an older fetch are hidden
+        let quotes = app
+            .conf
+            .economy
+            .instruments
+            .iter()
+            .filter_map(|id| app.economy.quotes.iter().find(|v| v.id == *id))
+            .collect::<Vec<_>>();
+
+        for quote in quotes {
+            let text_color = if is_stale {
+                app.theme.light_color
+            } else if quote.percent >= 0. {
+                app.theme.up_color
+            } else {
+                app.theme.down_color
+            };
+
+            ui.horizontal(|ui| {
+                StripBuilder::new(ui)
+                    .size(Size::relative(0.4))
+                    .size(Size::relative(0.2))
+                    .size(Size::relative(0.2))
+                    .size(Size::remainder())
+                    .horizontal(|mut strip| {
+                        strip.cell(|ui| {
+                            ui.label(
+                                RichText::new(instrument_name(locale, quote.id))
+                                    .color(text_color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                            );
+                        });
+
+                        strip.cell(|ui| sparkline(app, ui, quote));
+
+                        strip.cell(|ui| {
+                            ui.label(
+                                RichText::new(value_text(locale, quote))
+                                    .color(text_color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                            );
+                        });
+
+                        strip.cell(|ui| {
+                            ui.label(
+                                RichText::new(format::percent(locale, quote.percent))
+                                    .color(text_color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                            );
+                        });
+                    });
+            });
+        }
+    });
+}
+
+fn value_text(locale: Locale, quote: &Quote) -> String {
+    match quote.id.kind() {
+        InstrumentKind::Yield => format::percent(locale, quote.value),
+        _ => format::price(locale, quote.value),
+    }
+}
+
+// the recorded daily values of the instrument
+fn sparkline(app: &App, ui: &mut Ui, quote: &Quote) {
+    let points = app.economy_history.series(quote.id);
+    let height = ui.spacing().interact_size.y;
+    let (rect, resp) = ui.allocate_exact_size(vec2(ui.available_width(), height), Sense::hover());
+    if points.len() < 2 || !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (min, max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), p| {
+        (min.min(p.1), max.max(p.1))
+    });
+    let span = (max - min).max(f64::EPSILON);
+
+    let line = points
+        .iter()
+        .map(|&(ts, v)| {
+            pos2(
+                rect.left()
+                    + rect.width() * (ts - first.0) as f32 / (last.0 - first.0).max(1) as f32,
+                rect.bottom() - rect.height() * ((v - min) / span) as f32,
+            )
+        })
+        .collect();
+
+    let color = if last.1 >= first.1 {
+        app.theme.up_color
+    } else {
+        app.theme.down_color
+    };
+    ui.painter().add(Shape::line(line, Stroke::new(1.0, color)));
+
+    let locale = app.conf.ui.locale;
+    resp.on_hover_text(format!(
+        "{} - {}",
+        format::datetime(locale, first.0),
+        format::datetime(locale, last.0)
+    ));
+}
@@ -0,0 +1,288 @@
+use super::{app::App, app::MsgType, latest, tr::tr};
+use anyhow::Result;
+use egui::{ComboBox, Context, Window};
+use picon_core::{
+    config::{LatestColumn, Locale},
+    export::{self, Cell, ExportFormat, Table},
+    format,
+    history::DAY_SECS,
+    util,
+};
+use std::path::{Path, PathBuf};
+
+const HISTORY_DAYS: [u32; 4] = [0, 1, 7, 30];
+
+#[derive(Default, Debug, Clone)]
+pub struct Setting {
+    pub is_open: bool,
+}
+
+fn format_name(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Csv => "CSV",
+        ExportFormat::Json => "JSON",
+        ExportFormat::Xlsx => "XLSX",
+    }
+}
+
+fn history_name(locale: Locale, days: u32) -> String {
+    match days {
+        0 => tr(locale, "no_history_export"),
+        1 => tr(locale, "24h"),
+        7 => tr(locale, "7d"),
+        _ => tr(locale, "30d"),
+    }
+}
+
+// the visible columns in their order, the sparkline has no value
+fn columns(app: &App) -> Vec<LatestColumn> {
+    app.conf
+        .latest
+        .columns
+        .iter()
+        .filter(|c| c.is_visible && c.column != LatestColumn::Sparkline)
+        .map(|c| c.column)
+        .collect()
+}
+
+// the raw headers are the config names of the columns, e.g. "market_cap"
+fn column_key(column: LatestColumn) -> String {
+    serde_json::to_value(column)
+        .ok()
+        .and_then(|v| v.as_str().map(|v| v.to_string()))
+        .unwrap_or_default()
+}
+
+// the listings as they are sorted in the panel
+fn latest_table(app: &App) -> Table {
+    let locale = app.conf.ui.locale;
+    let is_formatted = app.conf.export.is_formatted;
+    let columns = columns(app);
+
+    let mut header = vec![if is_formatted {
+        tr(locale, "rank")
+    } else {
+        "rank".to_string()
+    }];
+    header.extend(columns.iter().map(|column| {
+        if is_formatted {
+            latest::column_name(locale, *column)
+        } else {
+            column_key(*column)
+        }
+    }));
+
+    let rows = app
+        .latest
+        .data
+        .iter()
+        .map(|item| {
+            let mut row = vec![Cell::Number(f64::from(item.rank))];
+            row.extend(columns.iter().map(|column| match column {
+                LatestColumn::Symbol => Cell::Text(item.symbol.clone()),
+                _ if is_formatted => {
+                    Cell::Text(latest::column_text(app, *column, item).unwrap_or_default())
+                }
+                _ => Cell::Number(latest::column_value(app, *column, item)),
+            }));
+            row
+        })
+        .collect();
+
+    Table {
+        name: "latest".to_string(),
+        header,
+        rows,
+    }
+}
+
+// the local snapshots of the listed coins, oldest first
+fn history_table(app: &App, days: u32) -> Table {
+    let locale = app.conf.ui.locale;
+    let is_formatted = app.conf.export.is_formatted;
+    let since = app.latest.addition_info.timestamp - i64::from(days) * DAY_SECS;
+
+    let header = if is_formatted {
+        vec![
+            tr(locale, "time"),
+            tr(locale, "symbol"),
+            tr(locale, "price"),
+        ]
+    } else {
+        vec![
+            "timestamp".to_string(),
+            "symbol".to_string(),
+            "price".to_string(),
+        ]
+    };
+
+    let mut rows = vec![];
+    for item in app.latest.data.iter() {
+        for (ts, price) in app.history.series(item.id, since) {
+            rows.push(if is_formatted {
+                vec![
+                    Cell::Text(format::datetime(locale, ts)),
+                    Cell::Text(item.symbol.clone()),
+                    Cell::Text(format::price(locale, price)),
+                ]
+            } else {
+                vec![
+                    Cell::Number(ts as f64),
+                    Cell::Text(item.symbol.clone()),
+                    Cell::Number(price),
+                ]
+            });
+        }
+    }
+
+    Table {
+        name: "history".to_string(),
+        header,
+        rows,
+    }
+}
+
+fn file_stem() -> String {
+    format!("picon-{}", util::timestamp())
+}
+
+// the file picked in a save dialog, `None` when the dialog is cancelled
+#[cfg(not(target_os = "android"))]
+fn target_path(app: &mut App) -> Option<PathBuf> {
+    let format = app.conf.export.format;
+    let ext = format.extension();
+    let mut path = rfd::FileDialog::new()
+        .set_directory(app.conf.export_dir())
+        .set_file_name(format!("{}.{ext}", file_stem()))
+        .add_filter(format_name(format), &[ext])
+        .save_file()?;
+
+    if path.extension().is_none_or(|v| v != ext) {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{ext}"));
+        path.set_file_name(name);
+    }
+
+    // the next dialog opens where this file went
+    if let Some(dir) = path.parent() {
+        app.conf.export.dir = dir.display().to_string();
+    }
+    Some(path)
+}
+
+// there is no save dialog, a new file goes to the shared directory
+#[cfg(target_os = "android")]
+fn target_path(app: &mut App) -> Option<PathBuf> {
+    Some(export::unused_path(
+        &app.conf.export_dir(),
+        &file_stem(),
+        app.conf.export.format,
+    ))
+}
+
+pub fn export(app: &App, path: &Path) -> Result<Vec<PathBuf>> {
+    let mut tables = vec![latest_table(app)];
+    if app.conf.export.history_days > 0 {
+        tables.push(history_table(app, app.conf.export.history_days));
+    }
+
+    export::write(path, app.conf.export.format, &tables)
+}
+
+pub fn ui(app: &mut App, ctx: &Context) {
+    if !app.export_setting.is_open {
+        return;
+    }
+
+    let locale = app.conf.ui.locale;
+    let (mut is_open, mut is_clicked) = (true, false);
+
+    Window::new(tr(locale, "export"))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            ComboBox::from_label(tr(locale, "file_format"))
+                .selected_text(format_name(app.conf.export.format))
+                .show_ui(ui, |ui| {
+                    for item in ExportFormat::ALL {
+                        ui.selectable_value(&mut app.conf.export.format, item, format_name(item));
+                    }
+                });
+
+            ui.checkbox(
+                &mut app.conf.export.is_formatted,
+                tr(locale, "formatted_values"),
+            );
+
+            ComboBox::from_label(tr(locale, "price_history"))
+                .selected_text(history_name(locale, app.conf.export.history_days))
+                .show_ui(ui, |ui| {
+                    for item in HISTORY_DAYS {
+                        ui.selectable_value(
+                            &mut app.conf.export.history_days,
+                            item,
+                            history_name(locale, item),
+                        );
+                    }
+                });
+
+            #[cfg(target_os = "android")]
+            {
+                let default_dir = app.conf.default_export_dir().display().to_string();
+                ui.add(egui::TextEdit::singleline(&mut app.conf.export.dir).hint_text(default_dir));
+            }
+
+            is_clicked = ui.button(tr(locale, "export")).clicked();
+        });
+
+    app.export_setting.is_open = is_open && !is_clicked;
+    if !is_clicked {
+        return;
+    }
+
+    let path = target_path(app);
+    if let Err(e) = app.conf.save() {
+        log::warn!("{e:?}");
+    }
+
+    let Some(path) = path else {
+        return;
+    };
+
+    match export(app, &path) {
+        Ok(paths) => {
+            let paths = paths.iter().map(|v| v.display().to_string());
+            let msg = format!(
+                "{}\n{}",
+                tr(locale, "exported_to"),
+                paths.collect::<Vec<_>>().join("\n")
+            );
+            app.show_message(msg, MsgType::Success);
+        }
+        Err(e) => app.show_message(format!("{e:?}"), MsgType::Warn),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use picon_core::{latest::Latest, test_support};
+
+    #[test]
+    fn raw_values_of_the_visible_columns() {
+        let mut app = App::new();
+        app.latest = serde_json::from_str::<Latest>(test_support::CMC_LISTINGS_LATEST).unwrap();
+        app.conf.export.is_formatted = false;
+
+        let table = latest_table(&app);
+        assert_eq!(table.header[..3], ["rank", "symbol", "price"]);
+        assert_eq!(table.rows.len(), 5);
+        assert_eq!(table.rows[0][1], Cell::Text("BTC".to_string()));
+        assert_eq!(table.rows[0][2], Cell::Number(61927.344528836584));
+
+        app.conf.export.is_formatted = true;
+        let table = latest_table(&app);
+        assert!(matches!(&table.rows[0][2], Cell::Text(v) if v.contains("61")));
+    }
+}
@@ -0,0 +1,131 @@
+use super::{app::App, theme, tr::tr};
+use anyhow::Result;
+use egui::{Color32, FontId, RichText, Ui};
+use egui_extras::{Size, StripBuilder};
+use picon_core::{
+    cache,
+    fees::{ChainFee, Fees, Operation, SOLANA_BASE_LAMPORTS},
+    format, util,
+};
+
+pub fn init(app: &mut App) {
+    if let Err(e) = load(app) {
+        log::debug!("{e:?}");
+    }
+}
+
+fn load(app: &mut App) -> Result<()> {
+    app.fees = cache::load::<Fees>(&app.conf.cache_dir.join("fees.json"))?;
+    Ok(())
+}
+
+// "21.00 + 1.00 Gwei" for the EVM chains, "5000 + 1000 µL/CU" for Solana
+fn fee_text(app: &App, fee: &ChainFee) -> String {
+    let locale = app.conf.ui.locale;
+    if fee.chain.is_evm() {
+        format!(
+            "{} + {} Gwei",
+            format::price(locale, fee.base_fee),
+            format::price(locale, fee.priority_fee)
+        )
+    } else {
+        format!(
+            "{} + {} µL/CU",
+            format::number(locale, SOLANA_BASE_LAMPORTS, 0),
+            format::number(locale, fee.priority_fee, 0)
+        )
+    }
+}
+
+// the currency of the listings which prices the costs, see `cost_text`
+fn cost_currency(app: &App) -> Option<&str> {
+    app.latest.data.first().map(|v| v.quote.currency.as_str())
+}
+
+// in the currency of the listings, or in the native coin when it is not listed
+fn cost_text(app: &App, fee: &ChainFee, op: Operation) -> String {
+    let locale = app.conf.ui.locale;
+    let cost = fee.cost(op);
+    let symbol = fee.chain.native_symbol();
+
+    match app.latest.data.iter().find(|v| v.symbol == symbol) {
+        Some(item) => format::money(
+            locale,
+            &item.quote.currency,
+            cost * item.quote.value.price,
+            false,
+        ),
+        None => format!("{} {symbol}", format::number(locale, cost, 6)),
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let is_stale = app.is_stale(app.fees.timestamp);
+
+    ui.vertical(|ui| {
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(format!(
+                    "{}({})",
+                    tr(locale, "fees"),
+                    util::data_age(app.fees.timestamp)
+                ))
+                .color(app.theme.text_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+            )
+            .on_hover_text(format::datetime(locale, app.fees.timestamp));
+        });
+
+        ui.separator();
+
+        let cost_header = |key| match cost_currency(app) {
+            Some(currency) => format!("{}({currency})", tr(locale, key)),
+            None => tr(locale, key),
+        };
+        let header = [
+            tr(locale, "chain"),
+            tr(locale, "base_priority_fee"),
+            cost_header("transfer"),
+            cost_header("swap"),
+        ];
+        row(ui, header, app.theme.light_color);
+
+        for fee in app.fees.items.iter() {
+            let cells = [
+                fee.chain.name().to_string(),
+                fee_text(app, fee),
+                cost_text(app, fee, Operation::Transfer),
+                cost_text(app, fee, Operation::Swap),
+            ];
+
+            let color = if is_stale {
+                app.theme.light_color
+            } else {
+                app.theme.text_color
+            };
+            row(ui, cells, color);
+        }
+    });
+}
+
+fn row(ui: &mut Ui, cells: [String; 4], color: Color32) {
+    ui.horizontal(|ui| {
+        StripBuilder::new(ui)
+            .size(Size::relative(0.2))
+            .size(Size::relative(0.36))
+            .size(Size::relative(0.22))
+            .size(Size::remainder())
+            .horizontal(|mut strip| {
+                for text in cells {
+                    strip.cell(|ui| {
+                        ui.label(
+                            RichText::new(text)
+                                .color(color)
+                                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                        );
+                    });
+                }
+            });
+    });
+}
@@ -0,0 +1,166 @@
+use super::{
+    app::App,
+    theme::{self, Theme},
+    tr::tr,
+};
+use egui::{pos2, vec2, Color32, FontId, Rect, RichText, Sense, Shape, Stroke, Ui};
+use picon_core::{
+    config::Locale,
+    format,
+    history::DAY_SECS,
+    stats::{GreedFear, GreedFearLevel},
+};
+
+const CHART_HEIGHT: f32 = 120.;
+
+// the comparisons with the latest value
+const COMPARISONS: [(i64, &str); 4] = [
+    (DAY_SECS, "yesterday"),
+    (7 * DAY_SECS, "7d_ago"),
+    (30 * DAY_SECS, "30d_ago"),
+    (365 * DAY_SECS, "1y_ago"),
+];
+
+pub fn level_name(locale: Locale, level: GreedFearLevel) -> String {
+    match level {
+        GreedFearLevel::ExtremeFear => tr(locale, "extreme_fear"),
+        GreedFearLevel::Fear => tr(locale, "fear"),
+        GreedFearLevel::Neutral => tr(locale, "neutral"),
+        GreedFearLevel::Greed => tr(locale, "greed"),
+        GreedFearLevel::ExtremeGreed => tr(locale, "extreme_greed"),
+    }
+}
+
+pub fn level_color(theme: &Theme, level: GreedFearLevel) -> Color32 {
+    match level {
+        GreedFearLevel::ExtremeFear => theme.down_color,
+        GreedFearLevel::Fear => theme.down_color.gamma_multiply(0.6),
+        GreedFearLevel::Neutral => theme.light_color,
+        GreedFearLevel::Greed => theme.up_color.gamma_multiply(0.6),
+        GreedFearLevel::ExtremeGreed => theme.up_color,
+    }
+}
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let greed_fear = &app.stats.crypto.greed_fear;
+
+    ui.vertical(|ui| {
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(tr(locale, "greed_fear"))
+                    .color(app.theme.text_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+            );
+        });
+
+        ui.separator();
+
+        let Some((_, value)) = greed_fear.latest() else {
+            ui.label(
+                RichText::new(tr(locale, "no_history"))
+                    .color(app.theme.light_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+            );
+            return;
+        };
+
+        let level = GreedFearLevel::from_value(value);
+        ui.vertical_centered(|ui| {
+            ui.label(
+                RichText::new(format!("{value} {}", level_name(locale, level)))
+                    .color(level_color(&app.theme, level))
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 6.)),
+            );
+        });
+
+        ui.add_space(theme::SPACING);
+        comparisons(app, ui, greed_fear);
+
+        ui.add_space(theme::SPACING);
+        chart(app, ui, greed_fear);
+    });
+}
+
+fn comparisons(app: &App, ui: &mut Ui, greed_fear: &GreedFear) {
+    let locale = app.conf.ui.locale;
+
+    ui.columns(COMPARISONS.len(), |columns| {
+        for (ui, (secs, key)) in columns.iter_mut().zip(COMPARISONS) {
+            let value = greed_fear.value_before(secs);
+            let color = value
+                .map(|v| level_color(&app.theme, GreedFearLevel::from_value(v)))
+                .unwrap_or(app.theme.light_color);
+
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    RichText::new(tr(locale, key))
+                        .color(app.theme.light_color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE * 0.8)),
+                );
+                ui.label(
+                    RichText::new(value.map_or("-".to_string(), |v| v.to_string()))
+                        .color(color)
+                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+                );
+            });
+        }
+    });
+}
+
+// the series over the classification bands, 0 at the bottom and 100 at the top
+fn chart(app: &App, ui: &mut Ui, greed_fear: &GreedFear) {
+    let points = greed_fear.points();
+    let (rect, resp) =
+        ui.allocate_exact_size(vec2(ui.available_width(), CHART_HEIGHT), Sense::hover());
+    if points.len() < 2 || !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let (first, last) = (points[0].0, points[points.len() - 1].0);
+    let to_x = |ts: i64| rect.left() + rect.width() * (ts - first) as f32 / (last - first) as f32;
+    let to_y = |v: f32| rect.bottom() - rect.height() * v / 100.;
+
+    let painter = ui.painter_at(rect);
+    let mut lower = 0.;
+    for (upper, level) in GreedFearLevel::BANDS {
+        let band = Rect::from_x_y_ranges(rect.x_range(), to_y(upper as f32)..=to_y(lower));
+        painter.rect_filled(
+            band,
+            0.,
+            level_color(&app.theme, level).gamma_multiply(0.25),
+        );
+        lower = upper as f32;
+    }
+
+    let line = points
+        .iter()
+        .map(|&(ts, v)| pos2(to_x(ts), to_y(v as f32)))
+        .collect();
+    painter.add(Shape::line(line, Stroke::new(1.5, app.theme.text_color)));
+
+    let Some(pointer) = resp.hover_pos() else {
+        return;
+    };
+
+    // the point nearest to the pointer
+    let Some(&(ts, value)) = points.iter().min_by(|a, b| {
+        (to_x(a.0) - pointer.x)
+            .abs()
+            .total_cmp(&(to_x(b.0) - pointer.x).abs())
+    }) else {
+        return;
+    };
+
+    let locale = app.conf.ui.locale;
+    painter.circle_filled(
+        pos2(to_x(ts), to_y(value as f32)),
+        3.,
+        app.theme.brand_color,
+    );
+    resp.on_hover_text(format!(
+        "{}\n{value} {}",
+        format::datetime(locale, ts),
+        level_name(locale, GreedFearLevel::from_value(value))
+    ));
+}
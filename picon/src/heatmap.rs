@@ -0,0 +1,249 @@
+use super::{app::App, theme, tr::tr};
+use egui::{
+    pos2, vec2, Align2, Color32, FontId, Rect, Rgba, RichText, Rounding, Sense, Stroke, Ui,
+};
+use picon_core::{config::HeatmapPeriod, format, latest::LatestDataItem};
+
+// the change at which a tile gets the full up/down color
+const FULL_COLOR_PERCENT: f64 = 10.;
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+
+    ui.horizontal(|ui| {
+        for (period, key) in [(HeatmapPeriod::H24, "24h"), (HeatmapPeriod::D7, "7d")] {
+            let is_selected = app.conf.latest.heatmap_period == period;
+            let text =
+                RichText::new(tr(locale, key)).font(FontId::proportional(theme::DEFAULT_FONT_SIZE));
+
+            if ui.selectable_label(is_selected, text).clicked() && !is_selected {
+                app.conf.latest.heatmap_period = period;
+                if let Err(e) = app.conf.save() {
+                    log::warn!("{e:?}");
+                }
+            }
+        }
+    });
+
+    ui.add_space(theme::SPACING);
+
+    // the largest coins first, so they get the squarest tiles
+    let mut items = app
+        .latest
+        .data
+        .iter()
+        .filter(|v| v.quote.value.market_cap.is_finite() && v.quote.value.market_cap > 0.)
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        b.quote
+            .value
+            .market_cap
+            .total_cmp(&a.quote.value.market_cap)
+    });
+
+    let (rect, resp) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+    let values = items
+        .iter()
+        .map(|v| v.quote.value.market_cap)
+        .collect::<Vec<_>>();
+    let tiles = treemap(&values, rect);
+
+    let is_stale = app.is_stale(app.latest.addition_info.timestamp);
+    let painter = ui.painter_at(rect);
+    let pointer = resp.hover_pos();
+    let mut hovered = None;
+
+    for (item, tile) in items.iter().zip(tiles.iter()) {
+        let change = change(item, app.conf.latest.heatmap_period);
+        let fill = if is_stale {
+            app.theme.light_color
+        } else {
+            tile_color(app, change)
+        };
+
+        painter.rect(
+            *tile,
+            Rounding::none(),
+            fill,
+            Stroke::new(1.0, ui.visuals().extreme_bg_color),
+        );
+
+        if pointer.is_some_and(|p| tile.contains(p)) {
+            hovered = Some(*item);
+        }
+
+        // only the tiles large enough for a label
+        if tile.width() > 36. && tile.height() > 28. {
+            let font_size = (tile.height().min(tile.width()) / 4.).clamp(
+                theme::DEFAULT_FONT_SIZE * 0.8,
+                theme::DEFAULT_FONT_SIZE * 2.,
+            );
+            painter.text(
+                tile.center(),
+                Align2::CENTER_CENTER,
+                format!("{}\n{}", item.symbol, format::percent(locale, change)),
+                FontId::proportional(font_size),
+                app.theme.text_color,
+            );
+        }
+    }
+
+    let Some(item) = hovered else {
+        return;
+    };
+
+    let value = &item.quote.value;
+    let currency = &item.quote.currency;
+    let resp = resp.on_hover_text(format!(
+        "{} ({})\n{}: {}\n{}: {}",
+        item.name,
+        item.symbol,
+        tr(locale, "price"),
+        format::money(locale, currency, value.price, false),
+        tr(locale, "market_cap"),
+        format::money(locale, currency, value.market_cap, true),
+    ));
+
+    if resp.clicked() {
+        open_coin(app, item);
+    }
+}
+
+fn change(item: &LatestDataItem, period: HeatmapPeriod) -> f64 {
+    match period {
+        HeatmapPeriod::H24 => item.quote.value.percent_change_24h,
+        HeatmapPeriod::D7 => item.quote.value.percent_change_7d,
+    }
+}
+
+// from the neutral color at 0% to the up/down color at `FULL_COLOR_PERCENT`
+fn tile_color(app: &App, change: f64) -> Color32 {
+    if !change.is_finite() {
+        return app.theme.light_color;
+    }
+
+    let color = if change >= 0. {
+        app.theme.up_color
+    } else {
+        app.theme.down_color
+    };
+
+    let t = (change.abs() / FULL_COLOR_PERCENT).clamp(0.15, 1.) as f32;
+    Color32::from(Rgba::from(app.theme.light_color) * (1. - t) + Rgba::from(color) * t)
+}
+
+fn open_coin(app: &App, item: &LatestDataItem) {
+    if item.slug.is_empty() {
+        return;
+    }
+
+    let url = format!("{}/currencies/{}/", app.conf.api.cmc_site, item.slug);
+    if let Err(e) = webbrowser::open(&url) {
+        log::warn!("{e:?}");
+    }
+}
+
+// Squarified treemap: one rect per value in the same order, each with an area
+// proportional to its value. The values should be positive and in descending order.
+pub fn treemap(values: &[f64], rect: Rect) -> Vec<Rect> {
+    let total = values.iter().sum::<f64>();
+    if values.is_empty() || total <= 0. || rect.area() <= 0. {
+        return vec![Rect::NOTHING; values.len()];
+    }
+
+    let scale = rect.area() as f64 / total;
+    let areas = values.iter().map(|v| v * scale).collect::<Vec<_>>();
+
+    let mut tiles = Vec::with_capacity(values.len());
+    let mut rest = rect;
+    let mut start = 0;
+
+    while start < areas.len() {
+        let side = rest.width().min(rest.height()) as f64;
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst_ratio(&areas[start..=end], side) <= worst_ratio(&areas[start..end], side)
+        {
+            end += 1;
+        }
+
+        let row = &areas[start..end];
+        let row_area = row.iter().sum::<f64>();
+
+        // the row fills the short side of the remaining rect
+        if rest.width() >= rest.height() {
+            let width = (row_area / rest.height() as f64) as f32;
+            let mut top = rest.top();
+            for area in row {
+                let height = (area / width as f64) as f32;
+                tiles.push(Rect::from_min_size(
+                    pos2(rest.left(), top),
+                    vec2(width, height),
+                ));
+                top += height;
+            }
+            rest.min.x += width;
+        } else {
+            let height = (row_area / rest.width() as f64) as f32;
+            let mut left = rest.left();
+            for area in row {
+                let width = (area / height as f64) as f32;
+                tiles.push(Rect::from_min_size(
+                    pos2(left, rest.top()),
+                    vec2(width, height),
+                ));
+                left += width;
+            }
+            rest.min.y += height;
+        }
+
+        start = end;
+    }
+
+    tiles
+}
+
+// the largest aspect ratio of the tiles when `row` is laid along `side`
+fn worst_ratio(row: &[f64], side: f64) -> f64 {
+    let sum = row.iter().sum::<f64>();
+    let (min, max) = row.iter().fold((f64::MAX, f64::MIN), |(min, max), v| {
+        (min.min(*v), max.max(*v))
+    });
+
+    let side2 = side * side;
+    (side2 * max / (sum * sum)).max(sum * sum / (side2 * min))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn treemap_fills_rect() {
+        let rect = Rect::from_min_size(pos2(10., 20.), vec2(600., 400.));
+        let values = [50., 25., 12., 8., 3., 2.];
+        let tiles = treemap(&values, rect);
+        assert_eq!(tiles.len(), values.len());
+
+        let total = values.iter().sum::<f64>();
+        for (value, tile) in values.iter().zip(tiles.iter()) {
+            let expected = rect.area() as f64 * value / total;
+            assert!((tile.area() as f64 - expected).abs() < 1., "{tile:?}");
+            assert!(rect.expand(0.01).contains_rect(*tile), "{tile:?}");
+        }
+
+        // no overlaps
+        for (i, a) in tiles.iter().enumerate() {
+            for b in tiles.iter().skip(i + 1) {
+                assert!(a.intersect(*b).area() < 0.01, "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn treemap_without_values() {
+        let rect = Rect::from_min_size(pos2(0., 0.), vec2(100., 100.));
+        assert!(treemap(&[], rect).is_empty());
+        assert_eq!(treemap(&[0., 0.], rect), vec![Rect::NOTHING; 2]);
+    }
+}
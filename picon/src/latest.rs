@@ -1,108 +1,60 @@
-use super::util;
-use super::{app::App, theme, tr::tr};
-use anyhow::{anyhow, Result};
+use super::{app::App, compare, export, heatmap, theme, tr::tr};
+use anyhow::Result;
 use egui::{
-    containers::scroll_area::ScrollBarVisibility, Button, FontId, ImageButton, RichText,
-    ScrollArea, Ui,
+    containers::scroll_area::ScrollBarVisibility, pos2, vec2, Button, Color32, Context, FontId,
+    ImageButton, Key, Pos2, RichText, ScrollArea, Sense, Shape, Stroke, TextureHandle, TextureId,
+    Ui, Window,
 };
 use egui_extras::{Size, StripBuilder};
-use reqwest::header::{HeaderMap, ACCEPT};
-use std::{collections::HashSet, fs, path::Path};
+use picon_core::{
+    cache,
+    config::{LatestColumn, LatestView, Locale},
+    format,
+    history::History,
+    icons,
+    latest::{Latest, LatestDataItem, SortContext, SortKey, SortSpec},
+    util,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 const LEFT_HEADER_WIDTH: f32 = 80.;
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
-pub enum SortKey {
-    Marker,
-    Rank,
-    Symbol,
-    Price,
-    H24,
-    D7,
-}
+// the uploaded logos beyond it are dropped, the least recently drawn first
+const MAX_ICON_TEXTURES: usize = 200;
 
-impl Default for SortKey {
-    fn default() -> Self {
-        SortKey::Rank
-    }
+// the downloads running at once, the other rows wait for a free slot
+const MAX_ICON_FETCHES: usize = 8;
+
+// a failed download is tried again after it, e.g. once the network is back
+pub const ICON_RETRY: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub enum CoinIcon {
+    Loading,
+    // the texture and the time it was last drawn
+    Loaded(TextureHandle, Instant),
+    Failed(Instant),
+    Missing,
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct Setting {
-    pub sort_key: SortKey,
     marker_symbols: HashSet<String>,
-}
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
-pub struct Latest {
-    pub status: LatestStatus,
+    // saves `marker_symbols.json` once the toggles settle
+    marker_writer: cache::Debounced<Vec<String>>,
 
-    #[serde(default)]
-    pub data: Vec<LatestDataItem>,
+    // normalized 7d trend of each coin, rebuilt after `App::history` changes
+    sparklines: HashMap<u64, Vec<Pos2>>,
 
-    #[serde(skip)]
-    pub addition_info: AdditionInfo,
-}
+    // coin id -> amount, saved to `holdings.json`
+    holdings: HashMap<u64, f64>,
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
-pub struct LatestStatus {
-    pub timestamp: String,
-    pub error_message: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
-pub struct LatestDataItem {
-    pub id: u64,
-    pub symbol: String,
-
-    #[serde(rename(deserialize = "cmc_rank"), rename(serialize = "cmc_rank"))]
-    pub rank: u32,
-
-    pub quote: LatestDataItemQuote,
-}
-
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
-pub struct LatestDataItemQuote {
-    #[serde(rename(deserialize = "USD"), rename(serialize = "USD"))]
-    pub usd: LatestDataItemQuoteUSD,
-}
-
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
-pub struct LatestDataItemQuoteUSD {
-    pub price: f64,
-    pub percent_change_24h: f64,
-    pub percent_change_7d: f64,
-}
-
-#[derive(Default, Debug, Clone)]
-pub struct AdditionInfo {
-    pub h24_up_count: usize,
-    pub d7_up_count: usize,
-    pub timestamp: i64,
-}
-
-// curl -H "X-CMC_PRO_API_KEY: $API_KEY" -H "Accept: application/json" -d "start=1&limit=100&convert=USD&aux=cmc_rank" -G https://pro-api.coinmarketcap.com/v1/cryptocurrency/listings/latest
-pub fn fetch(api_key: &str, save_path: &Path) -> Result<Latest> {
-    const API: &str = "https://pro-api.coinmarketcap.com/v1/cryptocurrency/listings/latest";
-
-    let client = reqwest::blocking::Client::new();
-    let resp = client
-        .get(API)
-        .headers(headers(api_key))
-        .query(&[
-            ("start", "1"),
-            ("limit", "100"),
-            ("convert", "USD"),
-            ("aux", "cmc_rank"),
-        ])
-        .send()?
-        .json::<Latest>()?;
-
-    if resp.status.error_message.is_none() {
-        _ = save(save_path, &resp);
-    }
-
-    Ok(resp)
+    // (coin id, symbol, amount text) of the open holding editor
+    editing_holding: Option<(u64, String, String)>,
 }
 
 pub fn init(app: &mut App) {
@@ -114,100 +66,164 @@ pub fn init(app: &mut App) {
         log::debug!("{e:?}");
     }
 
-    update_addition_info(app);
-    sort_by_key(app, SortKey::Marker, false);
+    match cache::load::<HashMap<u64, f64>>(&app.conf.cache_dir.join("holdings.json")) {
+        Ok(holdings) => app.latest_setting.holdings = holdings,
+        Err(e) => log::debug!("{e:?}"),
+    }
+
+    app.latest.update_addition_info();
+    record_history(app);
+    sort(app);
 }
 
 fn load_marker_symbols(app: &mut App) -> Result<()> {
     let path = app.conf.cache_dir.join("marker_symbols.json");
-    let text = fs::read_to_string(path)?;
-    app.latest_setting.marker_symbols = serde_json::from_str::<Vec<String>>(&text)?
-        .into_iter()
-        .collect();
+    app.latest_setting.marker_symbols = cache::load::<Vec<String>>(&path)?.into_iter().collect();
 
     Ok(())
 }
 
 fn load_latest(app: &mut App) -> Result<()> {
-    let path = app.conf.cache_dir.join("latest.json");
-    let text = fs::read_to_string(path)?;
-    app.latest = serde_json::from_str::<Latest>(&text)?;
-
+    app.latest = cache::load::<Latest>(&app.conf.cache_dir.join("latest.json"))?;
     Ok(())
 }
 
-fn save(path: &Path, latest: &Latest) -> Result<()> {
-    match serde_json::to_string(latest) {
-        Ok(text) => Ok(fs::write(path, text)?),
-        Err(e) => Err(anyhow!("{e:?}")),
+// the history file is switched when the currency of the listings changes
+pub fn record_history(app: &mut App) {
+    let Some(currency) = app.latest.data.first().map(|v| v.quote.currency.clone()) else {
+        return;
+    };
+
+    let path = app.conf.cache_dir.join(History::file_name(&currency));
+    if app.history.currency != currency {
+        app.history = cache::load::<History>(&path).unwrap_or_else(|e| {
+            log::debug!("{e:?}");
+            History::new(&currency)
+        });
+    }
+
+    app.history.record(&app.latest);
+    app.latest_setting.sparklines.clear();
+
+    if let Err(e) = cache::save(&path, &app.history) {
+        log::warn!("{e:?}");
     }
 }
 
-fn headers(api_key: &str) -> HeaderMap {
-    let mut headers = HeaderMap::new();
-    headers.insert(ACCEPT, "application/json".parse().unwrap());
-    headers.insert("X-CMC_PRO_API_KEY", api_key.parse().unwrap());
-    headers
+// the sort keys kept in `conf.latest.sort`, the primary one first
+const MAX_SORT_KEYS: usize = 3;
+
+pub fn sort(app: &mut App) {
+    app.latest.sort(
+        &app.conf.latest.sort,
+        &SortContext {
+            marker_symbols: &app.latest_setting.marker_symbols,
+            holdings: &app.latest_setting.holdings,
+            local_high: &app.history.local_high,
+        },
+    );
 }
 
-pub fn sort_by_key(app: &mut App, key: SortKey, is_reverse: bool) {
-    if is_reverse && app.latest_setting.sort_key == key {
-        app.latest.data.reverse();
-        return;
+// the primary key changes direction, any other key becomes the primary one
+pub fn toggle_sort(app: &mut App, key: SortKey) {
+    let specs = &mut app.conf.latest.sort;
+    match specs.first_mut() {
+        Some(spec) if spec.key == key => spec.is_descending = !spec.is_descending,
+        _ => {
+            specs.retain(|v| v.key != key);
+            specs.insert(0, SortSpec::asc(key));
+            specs.truncate(MAX_SORT_KEYS);
+        }
     }
 
-    app.latest_setting.sort_key = key;
-
-    match app.latest_setting.sort_key {
-        SortKey::Rank => app.latest.data.sort_by(|a, b| a.rank.cmp(&b.rank)),
-        SortKey::Symbol => app
-            .latest
-            .data
-            .sort_by(|a, b| a.symbol.to_uppercase().cmp(&b.symbol.to_uppercase())),
-        SortKey::Price => app.latest.data.sort_by(|a, b| {
-            a.quote
-                .usd
-                .price
-                .partial_cmp(&b.quote.usd.price)
-                .unwrap_or(std::cmp::Ordering::Less)
-        }),
-        SortKey::H24 => app.latest.data.sort_by(|a, b| {
-            a.quote
-                .usd
-                .percent_change_24h
-                .partial_cmp(&b.quote.usd.percent_change_24h)
-                .unwrap_or(std::cmp::Ordering::Less)
-        }),
-        SortKey::D7 => app.latest.data.sort_by(|a, b| {
-            a.quote
-                .usd
-                .percent_change_7d
-                .partial_cmp(&b.quote.usd.percent_change_7d)
-                .unwrap_or(std::cmp::Ordering::Less)
-        }),
-        _ => {
-            app.latest.data.sort_by(|a, b| a.rank.cmp(&b.rank));
+    if let Err(e) = app.conf.save() {
+        log::warn!("{e:?}");
+    }
+    sort(app);
+}
 
-            app.latest.data.sort_by(|a, b| {
-                let am = app.latest_setting.marker_symbols.contains(&a.symbol);
-                let bm = app.latest_setting.marker_symbols.contains(&b.symbol);
-                bm.cmp(&am)
-            });
-        }
+// ▲/▼ for the primary key, △/▽ for the secondary ones
+fn sort_arrow(app: &App, key: SortKey) -> &'static str {
+    match app.conf.latest.sort.iter().position(|v| v.key == key) {
+        Some(0) if app.conf.latest.sort[0].is_descending => "▼",
+        Some(0) => "▲",
+        Some(i) if app.conf.latest.sort[i].is_descending => "▽",
+        Some(_) => "△",
+        None => "",
     }
 }
 
 pub fn ui(app: &mut App, ui: &mut Ui) {
-    list_header(app, ui);
-    list_body(app, ui);
+    match app.conf.latest.view {
+        LatestView::List => {
+            list_header(app, ui);
+            list_body(app, ui);
+            holding_editor(app, ui.ctx());
+        }
+        LatestView::Heatmap => heatmap::ui(app, ui),
+    }
+
+    export::ui(app, ui.ctx());
+}
+
+// the amount of a coin, removed when it is empty or zero
+fn holding_editor(app: &mut App, ctx: &Context) {
+    let Some((coin_id, symbol, mut amount)) = app.latest_setting.editing_holding.take() else {
+        return;
+    };
+
+    let locale = app.conf.ui.locale;
+    let (mut is_open, mut is_done) = (true, false);
+
+    Window::new(format!("{} {symbol}", tr(locale, "holdings")))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let resp = ui.text_edit_singleline(&mut amount);
+                if resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    is_done = true;
+                }
+
+                if ui.button(tr(locale, "ok")).clicked() {
+                    is_done = true;
+                }
+            });
+        });
+
+    if !is_done {
+        if is_open {
+            app.latest_setting.editing_holding = Some((coin_id, symbol, amount));
+        }
+        return;
+    }
+
+    match amount.trim().parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0. => _ = app.latest_setting.holdings.insert(coin_id, v),
+        _ if amount.trim().is_empty() => _ = app.latest_setting.holdings.remove(&coin_id),
+        Ok(_) => _ = app.latest_setting.holdings.remove(&coin_id),
+        Err(_) => {
+            app.latest_setting.editing_holding = Some((coin_id, symbol, amount));
+            return;
+        }
+    }
+
+    let path = app.conf.cache_dir.join("holdings.json");
+    if let Err(e) = cache::save(&path, &app.latest_setting.holdings) {
+        log::warn!("{e:?}");
+    }
 }
 
 fn list_header(app: &mut App, ui: &mut Ui) {
-    let is_cn = app.conf.ui.is_cn;
-    let text_color = if app.latest.addition_info.h24_up_count >= 50 {
-        theme::UP_COLOR
+    let locale = app.conf.ui.locale;
+    let updated_at = app.latest.addition_info.timestamp;
+    let text_color = if app.is_stale(updated_at) {
+        app.theme.light_color
+    } else if up_percent(app, app.latest.addition_info.h24_up_count) >= 50 {
+        app.theme.up_color
     } else {
-        theme::DOWN_COLOR
+        app.theme.down_color
     };
 
     ui.horizontal(|ui| {
@@ -218,20 +234,20 @@ fn list_header(app: &mut App, ui: &mut Ui) {
                 strip.cell(|ui| {
                     let items = vec![
                         (SortKey::Marker, "...".to_string()),
-                        (SortKey::Rank, tr(is_cn, "排名")),
+                        (SortKey::Rank, tr(locale, "rank")),
                     ];
                     ui.columns(items.len(), |columns| {
                         for (i, v) in items.into_iter().enumerate() {
                             columns[i].horizontal(|ui| {
                                 let btn = Button::new(
-                                    RichText::new(v.1)
+                                    RichText::new(format!("{}{}", v.1, sort_arrow(app, v.0)))
                                         .color(text_color)
                                         .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
                                 )
                                 .frame(false);
 
                                 if ui.add(btn).clicked() {
-                                    sort_by_key(app, v.0, true);
+                                    toggle_sort(app, v.0);
                                 }
                             });
                         }
@@ -239,47 +255,31 @@ fn list_header(app: &mut App, ui: &mut Ui) {
                 });
 
                 strip.cell(|ui| {
-                    let items = vec![
-                        (SortKey::Symbol, tr(is_cn, "代币")),
-                        (
-                            SortKey::Price,
-                            format!(
-                                "{}({})",
-                                tr(is_cn, "价格"),
-                                util::short_time(util::timelapse(
-                                    app.latest.addition_info.timestamp
-                                ))
-                            ),
-                        ),
-                        (
-                            SortKey::H24,
-                            format!(
-                                "{}({}%)",
-                                tr(is_cn, "24h"),
-                                app.latest.addition_info.h24_up_count
-                            ),
-                        ),
-                        (
-                            SortKey::D7,
-                            format!(
-                                "{}({}%)",
-                                tr(is_cn, "7d"),
-                                app.latest.addition_info.d7_up_count
-                            ),
-                        ),
-                    ];
-                    ui.columns(items.len(), |columns| {
-                        for (i, v) in items.into_iter().enumerate() {
-                            columns[i].horizontal(|ui| {
-                                let btn = Button::new(
-                                    RichText::new(v.1)
-                                        .color(text_color)
-                                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
-                                )
-                                .frame(false);
+                    let columns = visible_columns(app, ui.available_width());
+                    column_strip(ui, &columns).horizontal(|mut strip| {
+                        for (column, _) in columns.iter() {
+                            strip.cell(|ui| {
+                                let (key, text) = column_header(app, *column);
+                                let arrow = key.map(|key| sort_arrow(app, key)).unwrap_or_default();
+                                let text = RichText::new(format!("{text}{arrow}"))
+                                    .color(text_color)
+                                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE));
+
+                                let Some(key) = key else {
+                                    ui.label(text);
+                                    return;
+                                };
+
+                                let mut resp = ui.add(Button::new(text).frame(false));
+                                if key == SortKey::Price && updated_at > 0 {
+                                    resp = resp.on_hover_text(format::datetime(locale, updated_at));
+                                }
+                                if let Some(hint) = column_hint(locale, *column) {
+                                    resp = resp.on_hover_text(hint);
+                                }
 
-                                if ui.add(btn).clicked() {
-                                    sort_by_key(app, v.0, true);
+                                if resp.clicked() {
+                                    toggle_sort(app, key);
                                 }
                             });
                         }
@@ -291,6 +291,195 @@ fn list_header(app: &mut App, ui: &mut Ui) {
     ui.add_space(theme::SPACING);
 }
 
+// the widths are relative, scaled to fill the panel
+fn visible_columns(app: &App, width: f32) -> Vec<(LatestColumn, f32)> {
+    let columns = app
+        .conf
+        .latest
+        .columns
+        .iter()
+        .filter(|c| c.is_visible)
+        .map(|c| (c.column, c.width.max(1.)))
+        .collect::<Vec<_>>();
+
+    let total = columns.iter().map(|c| c.1).sum::<f32>();
+    columns
+        .into_iter()
+        .map(|(column, w)| (column, w * width / total))
+        .collect()
+}
+
+fn column_strip<'a>(ui: &'a mut Ui, columns: &[(LatestColumn, f32)]) -> StripBuilder<'a> {
+    columns
+        .iter()
+        .fold(StripBuilder::new(ui).clip(true), |builder, (_, width)| {
+            builder.size(Size::exact(*width))
+        })
+}
+
+pub fn column_name(locale: Locale, column: LatestColumn) -> String {
+    match column {
+        LatestColumn::Symbol => tr(locale, "symbol"),
+        LatestColumn::Price => tr(locale, "price"),
+        LatestColumn::H1 => tr(locale, "1h"),
+        LatestColumn::H24 => tr(locale, "24h"),
+        LatestColumn::D7 => tr(locale, "7d"),
+        LatestColumn::D30 => tr(locale, "30d"),
+        LatestColumn::MarketCap => tr(locale, "market_cap"),
+        LatestColumn::Volume24h => tr(locale, "volume_24h"),
+        LatestColumn::CirculatingSupply => tr(locale, "circulating_supply"),
+        LatestColumn::LocalHighDistance => tr(locale, "local_high_distance"),
+        LatestColumn::Holdings => tr(locale, "holdings"),
+        LatestColumn::Sparkline => tr(locale, "sparkline_7d"),
+    }
+}
+
+// shown on hover wherever the column is named
+pub fn column_hint(locale: Locale, column: LatestColumn) -> Option<String> {
+    match column {
+        LatestColumn::LocalHighDistance => Some(tr(locale, "local_high_distance_hint")),
+        _ => None,
+    }
+}
+
+// the share of the listed coins counted as up, the list holds `conf.latest.limit` coins
+fn up_percent(app: &App, count: usize) -> usize {
+    match app.latest.data.len() {
+        0 => 0,
+        len => count * 100 / len,
+    }
+}
+
+// `None` for the columns which can not be sorted
+fn column_header(app: &App, column: LatestColumn) -> (Option<SortKey>, String) {
+    let locale = app.conf.ui.locale;
+    let info = &app.latest.addition_info;
+    let name = column_name(locale, column);
+
+    match column {
+        LatestColumn::Symbol => (Some(SortKey::Symbol), name),
+        LatestColumn::Price => (
+            Some(SortKey::Price),
+            format!("{name}({})", util::data_age(info.timestamp)),
+        ),
+        LatestColumn::H1 => (Some(SortKey::H1), name),
+        LatestColumn::H24 => (
+            Some(SortKey::H24),
+            format!("{name}({}%)", up_percent(app, info.h24_up_count)),
+        ),
+        LatestColumn::D7 => (
+            Some(SortKey::D7),
+            format!("{name}({}%)", up_percent(app, info.d7_up_count)),
+        ),
+        LatestColumn::D30 => (Some(SortKey::D30), name),
+        LatestColumn::MarketCap => (Some(SortKey::MarketCap), name),
+        LatestColumn::Volume24h => (Some(SortKey::Volume24h), name),
+        LatestColumn::CirculatingSupply => (Some(SortKey::CirculatingSupply), name),
+        LatestColumn::LocalHighDistance => (Some(SortKey::LocalHighDistance), name),
+        LatestColumn::Holdings => (Some(SortKey::Holdings), name),
+        LatestColumn::Sparkline => (None, name),
+    }
+}
+
+// the text of a cell as shown in the list, `None` for the sparkline and a missing holding
+pub fn column_text(app: &App, column: LatestColumn, data: &LatestDataItem) -> Option<String> {
+    let locale = app.conf.ui.locale;
+    let value = &data.quote.value;
+    let currency = &data.quote.currency;
+
+    let text = match column {
+        LatestColumn::Symbol => data.symbol.clone(),
+        LatestColumn::Price => format::price(locale, value.price),
+        LatestColumn::H1 => format::percent(locale, value.percent_change_1h),
+        LatestColumn::H24 => format::percent(locale, value.percent_change_24h),
+        LatestColumn::D7 => format::percent(locale, value.percent_change_7d),
+        LatestColumn::D30 => format::percent(locale, value.percent_change_30d),
+        LatestColumn::MarketCap => format::money(locale, currency, value.market_cap, true),
+        LatestColumn::Volume24h => format::money(locale, currency, value.volume_24h, true),
+        LatestColumn::CirculatingSupply => data
+            .circulating_supply
+            .map(|v| format::compact(locale, v))
+            .unwrap_or("-".to_string()),
+        LatestColumn::LocalHighDistance => data
+            .local_high_distance(&app.history.local_high)
+            .map(|v| format::percent(locale, v))
+            .unwrap_or("-".to_string()),
+        LatestColumn::Holdings => data
+            .holding_value(&app.latest_setting.holdings)
+            .map(|v| format::money(locale, currency, v, false))?,
+        LatestColumn::Sparkline => return None,
+    };
+    Some(text)
+}
+
+// the value of a cell before formatting, NaN when it is unknown
+pub fn column_value(app: &App, column: LatestColumn, data: &LatestDataItem) -> f64 {
+    let ctx = SortContext {
+        marker_symbols: &app.latest_setting.marker_symbols,
+        holdings: &app.latest_setting.holdings,
+        local_high: &app.history.local_high,
+    };
+
+    match column_header(app, column).0 {
+        Some(key) => data.sort_value(key, &ctx),
+        None => f64::NAN,
+    }
+}
+
+fn column_cell(
+    app: &mut App,
+    ui: &mut Ui,
+    column: LatestColumn,
+    data: &LatestDataItem,
+    text_color: Color32,
+    icon: Option<TextureId>,
+) {
+    let text = match column {
+        LatestColumn::Symbol => {
+            if let Some(icon) = icon {
+                ui.image(icon, theme::ICON_SIZE * 0.8);
+            }
+
+            if data.symbol.len() > 6 {
+                data.symbol[..6].to_string()
+            } else {
+                data.symbol.clone()
+            }
+        }
+        LatestColumn::Holdings => {
+            let text = column_text(app, column, data).unwrap_or("+".to_string());
+            let btn = Button::new(
+                RichText::new(text)
+                    .color(text_color)
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+            )
+            .frame(false);
+
+            if ui.add(btn).clicked() {
+                let amount = app
+                    .latest_setting
+                    .holdings
+                    .get(&data.id)
+                    .map(|v| v.to_string())
+                    .unwrap_or_default();
+                app.latest_setting.editing_holding = Some((data.id, data.symbol.clone(), amount));
+            }
+            return;
+        }
+        LatestColumn::Sparkline => {
+            sparkline(app, ui, data);
+            return;
+        }
+        _ => column_text(app, column, data).unwrap_or_default(),
+    };
+
+    ui.label(
+        RichText::new(text)
+            .color(text_color)
+            .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+    );
+}
+
 fn list_body(app: &mut App, ui: &mut Ui) {
     let num_rows = app.latest.data.len();
     if num_rows == 0 {
@@ -314,13 +503,81 @@ fn list_body(app: &mut App, ui: &mut Ui) {
     });
 }
 
+// read from `conf.icons_dir` or downloaded the first time the row is visible
+fn coin_icon(app: &mut App, ctx: &Context, coin_id: u64) -> Option<TextureId> {
+    match app.coin_icons.get_mut(&coin_id) {
+        Some(CoinIcon::Loaded(texture, drawn_at)) => {
+            *drawn_at = Instant::now();
+            return Some(texture.id());
+        }
+        Some(CoinIcon::Failed(at)) if at.elapsed() >= ICON_RETRY => (),
+        Some(_) => return None,
+        None => (),
+    }
+
+    match icons::load(&app.conf.icons_dir, coin_id) {
+        Some(bytes) => match theme::try_load_image_from_memory(&bytes) {
+            Ok(image) => {
+                let texture =
+                    ctx.load_texture(format!("coin-icon-{coin_id}"), image, Default::default());
+                let id = texture.id();
+                app.coin_icons
+                    .insert(coin_id, CoinIcon::Loaded(texture, Instant::now()));
+                evict_icon_textures(app);
+                Some(id)
+            }
+            Err(e) => {
+                log::warn!("icon {coin_id}: {e:?}");
+                app.coin_icons.insert(coin_id, CoinIcon::Missing);
+                None
+            }
+        },
+        None if app.conf.net.is_offline => None,
+        None if app.icon_fetch_count() >= MAX_ICON_FETCHES => None,
+        None => {
+            app.coin_icons.insert(coin_id, CoinIcon::Loading);
+            app.fetch_icon(coin_id);
+            None
+        }
+    }
+}
+
+fn evict_icon_textures(app: &mut App) {
+    let mut loaded = app
+        .coin_icons
+        .iter()
+        .filter_map(|(&id, icon)| match icon {
+            CoinIcon::Loaded(_, drawn_at) => Some((*drawn_at, id)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if loaded.len() > MAX_ICON_TEXTURES {
+        loaded.sort();
+        for (_, id) in &loaded[..loaded.len() - MAX_ICON_TEXTURES] {
+            app.coin_icons.remove(id);
+        }
+    }
+}
+
+// drops the logos of the coins no longer listed, e.g. after the limit is lowered
+pub fn retain_icons(app: &mut App) {
+    let ids = app.latest.data.iter().map(|d| d.id).collect::<HashSet<_>>();
+    app.coin_icons.retain(|id, _| ids.contains(id));
+}
+
 fn list_item(app: &mut App, ui: &mut Ui, row: usize) {
     let data = app.latest.data[row].clone();
-
-    let text_color = if data.quote.usd.percent_change_24h >= 0. {
-        theme::UP_COLOR
+    let icon = coin_icon(app, ui.ctx(), data.id);
+    let updated_at = util::utc_seconds_from_str(&data.last_updated)
+        .unwrap_or(app.latest.addition_info.timestamp);
+
+    let text_color = if app.is_stale(updated_at) {
+        app.theme.light_color
+    } else if data.quote.value.percent_change_24h >= 0. {
+        app.theme.up_color
     } else {
-        theme::DOWN_COLOR
+        app.theme.down_color
     };
 
     let marker_icon_id = if app.latest_setting.marker_symbols.contains(&data.symbol) {
@@ -345,33 +602,33 @@ fn list_item(app: &mut App, ui: &mut Ui, row: usize) {
                             update_marker_symbols(app, &data.symbol);
                         };
 
-                        columns[1].label(
-                            RichText::new(&format!("{}", data.rank))
-                                .color(text_color)
-                                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
-                        );
+                        // picks the coin for the comparison
+                        let mut rank = RichText::new(format!("{}", data.rank))
+                            .color(text_color)
+                            .font(FontId::proportional(theme::DEFAULT_FONT_SIZE));
+                        if compare::is_picked(app, data.id) {
+                            rank = rank.color(app.theme.brand_color).underline();
+                        }
+                        let btn = Button::new(rank).frame(false);
+
+                        if columns[1]
+                            .add(btn)
+                            .on_hover_text(tr(app.conf.ui.locale, "compare"))
+                            .clicked()
+                        {
+                            compare::toggle(app, data.id);
+                        }
                     });
                 });
 
                 strip.cell(|ui| {
-                    let items = vec![
-                        if data.symbol.len() > 6 {
-                            data.symbol[..6].to_string()
-                        } else {
-                            data.symbol.clone()
-                        },
-                        util::pretty_price(data.quote.usd.price),
-                        util::pretty_precent(data.quote.usd.percent_change_24h),
-                        util::pretty_precent(data.quote.usd.percent_change_7d),
-                    ];
-                    ui.columns(items.len(), |columns| {
-                        for (i, v) in items.into_iter().enumerate() {
-                            columns[i].horizontal(|ui| {
-                                ui.label(
-                                    RichText::new(&v)
-                                        .color(text_color)
-                                        .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
-                                );
+                    let columns = visible_columns(app, ui.available_width());
+                    column_strip(ui, &columns).horizontal(|mut strip| {
+                        for (column, _) in columns.iter() {
+                            strip.cell(|ui| {
+                                ui.horizontal(|ui| {
+                                    column_cell(app, ui, *column, &data, text_color, icon);
+                                });
                             });
                         }
                     });
@@ -382,42 +639,91 @@ fn list_item(app: &mut App, ui: &mut Ui, row: usize) {
     ui.add_space(theme::SPACING * 2.);
 }
 
-fn update_marker_symbols(app: &mut App, symbol: &str) {
+// x and y in 0..=1, y grows downwards like the screen
+fn sparkline_points(points: &[(i64, f64)]) -> Vec<Pos2> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return vec![];
+    };
+
+    let (min, max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), p| {
+        (min.min(p.1), max.max(p.1))
+    });
+    let span_x = (last.0 - first.0).max(1) as f64;
+    let span_y = if max > min { max - min } else { 1. };
+
+    points
+        .iter()
+        .map(|p| {
+            pos2(
+                ((p.0 - first.0) as f64 / span_x) as f32,
+                (1. - (p.1 - min) / span_y) as f32,
+            )
+        })
+        .collect()
+}
+
+fn sparkline(app: &mut App, ui: &mut Ui, data: &LatestDataItem) {
+    let now = app.latest.addition_info.timestamp;
+    let is_stale = app.is_stale(now);
+    let theme = app.theme;
+    let points = app
+        .latest_setting
+        .sparklines
+        .entry(data.id)
+        .or_insert_with(|| sparkline_points(&app.history.trend_7d(data, now)));
+
+    let height = ui.spacing().interact_size.y;
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), height), Sense::hover());
+    if points.len() < 2 || !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let color = if is_stale {
+        theme.light_color
+    } else if points[points.len() - 1].y <= points[0].y {
+        theme.up_color
+    } else {
+        theme.down_color
+    };
+
+    let line = points
+        .iter()
+        .map(|p| {
+            pos2(
+                rect.left() + p.x * rect.width(),
+                rect.top() + p.y * rect.height(),
+            )
+        })
+        .collect();
+    ui.painter().add(Shape::line(line, Stroke::new(1.0, color)));
+}
+
+pub fn update_marker_symbols(app: &mut App, symbol: &str) {
     if app.latest_setting.marker_symbols.contains(symbol) {
         app.latest_setting.marker_symbols.remove(symbol);
     } else {
         app.latest_setting.marker_symbols.insert(symbol.to_string());
     }
 
-    match serde_json::to_string(
-        &app.latest_setting
-            .marker_symbols
-            .iter()
-            .map(|v| v.clone())
-            .collect::<Vec<String>>(),
-    ) {
-        Ok(text) => {
-            let path = app.conf.cache_dir.join("marker_symbols.json");
-            _ = fs::write(path, text);
-        }
-        Err(e) => log::warn!("{e:?}"),
-    }
+    let symbols = app.latest_setting.marker_symbols.iter().cloned().collect();
+    let path = app.conf.cache_dir.join("marker_symbols.json");
+    app.latest_setting.marker_writer.set(path, symbols);
 }
 
-pub fn update_addition_info(app: &mut App) {
-    app.latest.addition_info.h24_up_count = app
-        .latest
-        .data
-        .iter()
-        .filter(|&v| v.quote.usd.percent_change_24h >= 0.)
-        .count();
+// writes the pending marker toggles right away, before the app may be killed
+pub fn save_markers(app: &mut App) {
+    if let Err(e) = app.latest_setting.marker_writer.flush() {
+        log::warn!("{e:?}");
+    }
+}
 
-    app.latest.addition_info.d7_up_count = app
-        .latest
-        .data
-        .iter()
-        .filter(|&v| v.quote.usd.percent_change_7d >= 0.)
-        .count();
+// writes the marker toggles that are due, and wakes the ui again for the pending ones
+pub fn flush_markers(app: &mut App, ctx: &Context) {
+    if let Err(e) = app.latest_setting.marker_writer.flush_due() {
+        log::warn!("{e:?}");
+    }
 
-    app.latest.addition_info.timestamp = util::timestamp();
+    if let Some(d) = app.latest_setting.marker_writer.due_in() {
+        ctx.request_repaint_after(d);
+    }
 }
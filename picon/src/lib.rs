@@ -1,8 +1,5 @@
 #![windows_subsystem = "windows"]
 
-#[macro_use]
-extern crate serde_derive;
-
 // #[macro_use]
 // extern crate lazy_static;
 
@@ -20,14 +17,23 @@ use crate::winit::platform::android::activity::AndroidApp;
 mod about;
 mod app;
 mod latest;
+mod heatmap;
 mod trending;
 mod stats;
+mod breadth;
+mod greed_fear;
+mod fees;
+mod economy;
+mod dominance;
+mod converter;
+mod compare;
+mod export;
+mod backup;
 mod theme;
 mod tr;
-mod util;
 mod version;
 mod apikey;
-mod config;
+mod setting;
 
 use app::App;
 
@@ -77,6 +83,11 @@ fn create_window<T>(
     Some(window)
 }
 
+// `None` when the platform does not report its theme
+fn is_system_dark(window: &winit::window::Window) -> Option<bool> {
+    window.theme().map(|theme| theme == winit::window::Theme::Dark)
+}
+
 fn _main(event_loop: EventLoop<Event>) {
     let ctx = egui::Context::default();
     theme::init(&ctx);
@@ -118,6 +129,7 @@ fn _main(event_loop: EventLoop<Event>) {
         Resumed => match window {
             None => {
                 window = create_window(event_loop, &mut state, &mut painter);
+                egui_windows.is_system_dark = window.as_ref().and_then(is_system_dark);
             }
             Some(ref window) => {
                 pollster::block_on(painter.set_window(Some(window))).unwrap_or_else(|err| {
@@ -129,6 +141,7 @@ fn _main(event_loop: EventLoop<Event>) {
             }
         },
         Suspended => {
+            latest::save_markers(&mut egui_windows);
             window = None;
         }
         RedrawRequested(..) => {
@@ -166,8 +179,12 @@ fn _main(event_loop: EventLoop<Event>) {
                     painter.on_window_resized(size.width, size.height);
                 }
                 winit::event::WindowEvent::CloseRequested => {
+                    latest::save_markers(&mut egui_windows);
                     *control_flow = ControlFlow::Exit;
                 }
+                winit::event::WindowEvent::ThemeChanged(theme) => {
+                    egui_windows.is_system_dark = Some(theme == winit::window::Theme::Dark);
+                }
                 _ => {}
             }
 
@@ -0,0 +1,420 @@
+use super::{
+    app::App,
+    backup, economy, latest, theme,
+    tr::{locale_name, tr},
+};
+use egui::{Button, Checkbox, ComboBox, FontId, Response, RichText, ScrollArea, Slider, Ui};
+use picon_core::{
+    config::{ColorConvention, Locale, ThemeMode},
+    economy::{EconomySource, InstrumentId, InstrumentKind},
+};
+
+pub const CURRENCIES: [&str; 7] = ["USD", "CNY", "EUR", "JPY", "KRW", "BTC", "ETH"];
+const LIMITS: [u32; 4] = [50, 100, 200, 500];
+const GREED_FEAR_LIMITS: [u32; 4] = [30, 90, 365, 1000];
+
+pub fn ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+
+    if ui
+        .add(
+            Button::image_and_text(
+                app.back_icon.clone().unwrap().id(),
+                theme::BACK_ICON_SIZE,
+                RichText::new(tr(locale, "setting"))
+                    .font(FontId::proportional(theme::DEFAULT_FONT_SIZE)),
+            )
+            .frame(false),
+        )
+        .clicked()
+    {
+        app.current_panel = app.prev_panel;
+    }
+
+    ui.add_space(theme::SPACING * 4.);
+
+    ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            theme_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            net_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            latest_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            columns_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            stats_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            economy_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            fees_ui(app, ui);
+            ui.add_space(theme::SPACING * 4.);
+            backup::ui(app, ui);
+        });
+}
+
+fn ui_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "language"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        ComboBox::from_id_source("setting-language")
+            .selected_text(locale_name(locale))
+            .show_ui(ui, |ui| {
+                for item in Locale::ALL {
+                    ui.selectable_value(&mut app.conf.ui.locale, item, locale_name(item));
+                }
+            });
+    });
+
+    if locale != app.conf.ui.locale {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+fn theme_name(locale: Locale, mode: ThemeMode) -> String {
+    match mode {
+        ThemeMode::Light => tr(locale, "theme_light"),
+        ThemeMode::Dark => tr(locale, "theme_dark"),
+        ThemeMode::System => tr(locale, "theme_system"),
+    }
+}
+
+fn convention_name(locale: Locale, convention: ColorConvention) -> String {
+    match convention {
+        ColorConvention::GreenUp => tr(locale, "green_up"),
+        ColorConvention::RedUp => tr(locale, "red_up"),
+        ColorConvention::BlueOrange => tr(locale, "blue_orange"),
+    }
+}
+
+fn theme_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let (mode, convention) = (app.conf.ui.theme, app.conf.ui.color_convention);
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "theme"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        ComboBox::from_id_source("setting-theme")
+            .selected_text(theme_name(locale, mode))
+            .show_ui(ui, |ui| {
+                for item in ThemeMode::ALL {
+                    ui.selectable_value(&mut app.conf.ui.theme, item, theme_name(locale, item));
+                }
+            });
+
+        ComboBox::from_label(tr(locale, "color_convention"))
+            .selected_text(convention_name(locale, convention))
+            .show_ui(ui, |ui| {
+                for item in ColorConvention::ALL {
+                    ui.selectable_value(
+                        &mut app.conf.ui.color_convention,
+                        item,
+                        convention_name(locale, item),
+                    );
+                }
+            });
+    });
+
+    // applied by `App::ui` on the next frame
+    if mode != app.conf.ui.theme || convention != app.conf.ui.color_convention {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+fn latest_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let (currency, limit) = (app.conf.latest.currency.clone(), app.conf.latest.limit);
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "latest"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        ComboBox::from_label(tr(locale, "currency"))
+            .selected_text(&app.conf.latest.currency)
+            .show_ui(ui, |ui| {
+                for item in CURRENCIES {
+                    ui.selectable_value(&mut app.conf.latest.currency, item.to_string(), item);
+                }
+            });
+
+        ComboBox::from_label(tr(locale, "count"))
+            .selected_text(app.conf.latest.limit.to_string())
+            .show_ui(ui, |ui| {
+                for item in LIMITS {
+                    ui.selectable_value(&mut app.conf.latest.limit, item, item.to_string());
+                }
+            });
+    });
+
+    // the running request is cancelled and reissued with the new settings
+    if currency != app.conf.latest.currency || limit != app.conf.latest.limit {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+        app.fetch_latest();
+    }
+}
+
+// the columns of the Latest panel, in display order
+fn columns_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let mut is_changed = false;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "columns"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        let count = app.conf.latest.columns.len();
+        let mut swap = None;
+
+        for i in 0..count {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(i > 0, Button::new("⬆").frame(false))
+                    .clicked()
+                {
+                    swap = Some((i - 1, i));
+                }
+
+                if ui
+                    .add_enabled(i + 1 < count, Button::new("⬇").frame(false))
+                    .clicked()
+                {
+                    swap = Some((i, i + 1));
+                }
+
+                let layout = &mut app.conf.latest.columns[i];
+                is_changed |= is_slider_committed(
+                    &ui.add(Slider::new(&mut layout.width, 40.0..=240.0).show_value(false)),
+                );
+
+                let column = layout.column;
+                let mut resp =
+                    ui.checkbox(&mut layout.is_visible, latest::column_name(locale, column));
+                if let Some(hint) = latest::column_hint(locale, column) {
+                    resp = resp.on_hover_text(hint);
+                }
+                is_changed |= resp.changed();
+            });
+        }
+
+        if let Some((a, b)) = swap {
+            app.conf.latest.columns.swap(a, b);
+            is_changed = true;
+        }
+    });
+
+    if is_changed {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+fn stats_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let limit = app.conf.stats.greed_fear_limit;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "stats"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        ComboBox::from_label(tr(locale, "greed_fear_days"))
+            .selected_text(limit.to_string())
+            .show_ui(ui, |ui| {
+                for item in GREED_FEAR_LIMITS {
+                    ui.selectable_value(
+                        &mut app.conf.stats.greed_fear_limit,
+                        item,
+                        item.to_string(),
+                    );
+                }
+            });
+    });
+
+    // used by the next stats fetch
+    if limit != app.conf.stats.greed_fear_limit {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+// the instruments are shown in the order they were checked
+fn economy_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let source = app.conf.economy.source;
+    let mut is_changed = false;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "economy"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        ComboBox::from_label(tr(locale, "data_source"))
+            .selected_text(economy::source_name(source))
+            .show_ui(ui, |ui| {
+                for item in EconomySource::ALL {
+                    ui.selectable_value(
+                        &mut app.conf.economy.source,
+                        item,
+                        economy::source_name(item),
+                    );
+                }
+            });
+        is_changed |= source != app.conf.economy.source;
+
+        for kind in InstrumentKind::ALL {
+            ui.label(economy::kind_name(locale, kind));
+            ui.horizontal_wrapped(|ui| {
+                for id in InstrumentId::ALL.into_iter().filter(|v| v.kind() == kind) {
+                    let is_served = app.conf.economy.source.serves(id);
+                    let instruments = &mut app.conf.economy.instruments;
+                    let mut is_checked = is_served && instruments.contains(&id);
+                    if ui
+                        .add_enabled(
+                            is_served,
+                            Checkbox::new(&mut is_checked, economy::instrument_name(locale, id)),
+                        )
+                        .on_disabled_hover_text(tr(locale, "not_served_by_source"))
+                        .changed()
+                    {
+                        if is_checked {
+                            instruments.push(id);
+                        } else {
+                            instruments.retain(|v| *v != id);
+                        }
+                        is_changed = true;
+                    }
+                }
+            });
+        }
+    });
+
+    if is_changed {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+        app.fetch_economy();
+    }
+}
+
+// the JSON-RPC endpoints, e.g. a local node
+fn fees_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let mut is_changed = false;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "fees"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        for endpoint in app.conf.fees.endpoints.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.label(endpoint.chain.name());
+                let resp = ui.text_edit_singleline(&mut endpoint.url);
+
+                // the url from before the edit, so tabbing through the fields changes nothing
+                if resp.gained_focus() {
+                    ui.data_mut(|d| d.insert_temp(resp.id, endpoint.url.clone()));
+                }
+                if resp.lost_focus() {
+                    let before = ui.data_mut(|d| {
+                        let url = d.get_temp::<String>(resp.id);
+                        d.remove::<String>(resp.id);
+                        url
+                    });
+                    is_changed |= before.is_some_and(|url| url != endpoint.url);
+                }
+            });
+        }
+    });
+
+    if is_changed {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+        app.fetch_fees();
+    }
+}
+
+fn net_ui(app: &mut App, ui: &mut Ui) {
+    let locale = app.conf.ui.locale;
+    let mut is_changed = false;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(tr(locale, "network"))
+                .color(app.theme.title_color)
+                .font(FontId::proportional(theme::DEFAULT_FONT_SIZE + 3.)),
+        );
+        ui.separator();
+
+        let is_offline = app.conf.net.is_offline;
+        if ui
+            .checkbox(&mut app.conf.net.is_offline, tr(locale, "offline_mode"))
+            .changed()
+        {
+            is_changed = true;
+            if is_offline {
+                app.fetch_latest();
+            } else {
+                app.cancel_all();
+            }
+        }
+
+        let mut stale_mins = app.conf.net.stale_secs / 60;
+        is_changed |= is_slider_committed(&ui.add(
+            Slider::new(&mut stale_mins, 1..=24 * 60).text(tr(locale, "stale_after_minutes")),
+        ));
+        app.conf.net.stale_secs = stale_mins * 60;
+    });
+
+    if is_changed {
+        if let Err(e) = app.conf.save() {
+            log::warn!("{e:?}");
+        }
+    }
+}
+
+// a drag is saved once released, a click or a key press right away
+fn is_slider_committed(resp: &Response) -> bool {
+    resp.drag_released() || (resp.changed() && !resp.dragged())
+}
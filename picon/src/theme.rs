@@ -1,6 +1,7 @@
 use egui::{
     Color32, ColorImage, Context, FontData, FontDefinitions, FontFamily, Style, Vec2, Visuals,
 };
+use picon_core::config::{ColorConvention, ThemeMode};
 
 pub const PADDING: f32 = 4.;
 pub const SPACING: f32 = 4.;
@@ -9,17 +10,6 @@ pub const BACK_ICON_SIZE: Vec2 = Vec2::new(16.0, 16.0);
 
 pub const DEFAULT_FONT_SIZE: f32 = 14.0;
 
-pub const TITLE_COLOR: Color32 = Color32::from_rgb(0, 0, 200);
-pub const UP_COLOR: Color32 = Color32::DARK_GREEN;
-pub const DOWN_COLOR: Color32 = Color32::RED;
-
-pub const BRAND_COLOR: Color32 = Color32::from_rgb(0, 0, 139);
-pub const LIGHT_COLOR: Color32 = Color32::GRAY;
-pub const INFO_COLOR: Color32 = Color32::from_rgb(144, 144, 153);
-pub const SUCCESS_COLOR: Color32 = Color32::from_rgb(102, 194, 58);
-pub const WARN_COLOR: Color32 = Color32::from_rgb(230, 162, 60);
-pub const DANGER_COLOR: Color32 = Color32::from_rgb(245, 108, 108);
-
 pub const REFRESH_ICON: &[u8] = include_bytes!("./res/image/refresh.png");
 pub const LANGUAGE_ICON: &[u8] = include_bytes!("./res/image/lang.png");
 pub const BRAND_ICON: &[u8] = include_bytes!("./res/image/brand.png");
@@ -31,17 +21,101 @@ pub const LATEST_ICON: &[u8] = include_bytes!("./res/image/latest.png");
 pub const TRENDING_ICON: &[u8] = include_bytes!("./res/image/trending.png");
 pub const STATS_ICON: &[u8] = include_bytes!("./res/image/stats.png");
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub is_dark: bool,
+    pub text_color: Color32,
+    pub title_color: Color32,
+    pub up_color: Color32,
+    pub down_color: Color32,
+    pub brand_color: Color32,
+    pub light_color: Color32,
+    pub info_color: Color32,
+    pub success_color: Color32,
+    pub warn_color: Color32,
+    pub danger_color: Color32,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        is_dark: false,
+        text_color: Color32::BLACK,
+        title_color: Color32::from_rgb(0, 0, 200),
+        up_color: Color32::DARK_GREEN,
+        down_color: Color32::RED,
+        brand_color: Color32::from_rgb(0, 0, 139),
+        light_color: Color32::GRAY,
+        info_color: Color32::from_rgb(144, 144, 153),
+        success_color: Color32::from_rgb(102, 194, 58),
+        warn_color: Color32::from_rgb(230, 162, 60),
+        danger_color: Color32::from_rgb(245, 108, 108),
+    };
+
+    pub const DARK: Theme = Theme {
+        is_dark: true,
+        text_color: Color32::from_gray(220),
+        title_color: Color32::from_rgb(110, 160, 255),
+        up_color: Color32::from_rgb(70, 190, 100),
+        down_color: Color32::from_rgb(240, 90, 90),
+        brand_color: Color32::from_rgb(130, 160, 255),
+        light_color: Color32::from_gray(110),
+        info_color: Color32::from_rgb(96, 96, 104),
+        success_color: Color32::from_rgb(62, 130, 36),
+        warn_color: Color32::from_rgb(170, 112, 30),
+        danger_color: Color32::from_rgb(170, 60, 60),
+    };
+
+    // `is_system_dark` is `None` when the platform does not report it, such as X11 and Android
+    pub fn from_mode(mode: ThemeMode, is_system_dark: Option<bool>) -> Theme {
+        match mode {
+            ThemeMode::Light => Theme::LIGHT,
+            ThemeMode::Dark => Theme::DARK,
+            ThemeMode::System if is_system_dark == Some(true) => Theme::DARK,
+            ThemeMode::System => Theme::LIGHT,
+        }
+    }
+
+    pub fn with_convention(mut self, convention: ColorConvention) -> Theme {
+        match convention {
+            ColorConvention::GreenUp => (),
+            ColorConvention::RedUp => {
+                (self.up_color, self.down_color) = (self.down_color, self.up_color);
+            }
+            ColorConvention::BlueOrange if self.is_dark => {
+                self.up_color = Color32::from_rgb(86, 180, 233);
+                self.down_color = Color32::from_rgb(230, 159, 0);
+            }
+            ColorConvention::BlueOrange => {
+                self.up_color = Color32::from_rgb(0, 114, 178);
+                self.down_color = Color32::from_rgb(213, 94, 0);
+            }
+        }
+
+        self
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::LIGHT
+    }
+}
+
 pub fn init(ctx: &Context) {
     set_font(ctx);
-    set_style(ctx);
+    set_style(ctx, &Theme::default());
 }
 
-fn set_style(ctx: &Context) {
-    ctx.set_visuals(Visuals::light());
+pub fn set_style(ctx: &Context, theme: &Theme) {
+    ctx.set_visuals(if theme.is_dark {
+        Visuals::dark()
+    } else {
+        Visuals::light()
+    });
 
     let mut style: Style = (*ctx.style()).clone();
     style.spacing.scroll_bar_width = 2.0;
-    style.visuals.hyperlink_color = LIGHT_COLOR;
+    style.visuals.hyperlink_color = theme.light_color;
     ctx.set_style(style);
 }
 
@@ -63,9 +137,14 @@ fn set_font(ctx: &Context) {
 }
 
 pub fn load_image_from_memory(image_data: &[u8]) -> ColorImage {
-    let image = image::load_from_memory(image_data).unwrap();
+    try_load_image_from_memory(image_data).unwrap()
+}
+
+// for downloaded images, which may be broken
+pub fn try_load_image_from_memory(image_data: &[u8]) -> image::ImageResult<ColorImage> {
+    let image = image::load_from_memory(image_data)?;
     let size = [image.width() as _, image.height() as _];
     let image_buffer = image.to_rgba8();
     let pixels = image_buffer.as_flat_samples();
-    ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
+    Ok(ColorImage::from_rgba_unmultiplied(size, pixels.as_slice()))
 }
@@ -1,80 +1,129 @@
+use picon_core::config::Locale;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
-pub fn tr(is_cn: bool, text: &str) -> String {
-    if is_cn {
-        return text.to_string();
+type Messages = HashMap<String, String>;
+
+const LOCALE_FILES: [(Locale, &str); 5] = [
+    (Locale::En, include_str!("./res/locale/en.json")),
+    (Locale::ZhCN, include_str!("./res/locale/zh-CN.json")),
+    (Locale::ZhTW, include_str!("./res/locale/zh-TW.json")),
+    (Locale::Ja, include_str!("./res/locale/ja.json")),
+    (Locale::Ko, include_str!("./res/locale/ko.json")),
+];
+
+// parsed once on first use
+fn locales() -> &'static HashMap<Locale, Messages> {
+    static LOCALES: OnceLock<HashMap<Locale, Messages>> = OnceLock::new();
+
+    LOCALES.get_or_init(|| {
+        LOCALE_FILES
+            .iter()
+            .map(|(locale, text)| {
+                let messages = serde_json::from_str::<Messages>(text).unwrap_or_else(|e| {
+                    log::warn!("invalid locale file {locale:?}: {e:?}");
+                    Messages::default()
+                });
+                (*locale, messages)
+            })
+            .collect()
+    })
+}
+
+// falls back to English, and to the key itself when English misses it too
+pub fn tr(locale: Locale, key: &str) -> String {
+    let locales = locales();
+
+    if let Some(text) = locales.get(&locale).and_then(|m| m.get(key)) {
+        return text.clone();
+    }
+
+    match locales.get(&Locale::En).and_then(|m| m.get(key)) {
+        Some(text) => text.clone(),
+        None => {
+            log::debug!("missing translation: {key}");
+            key.to_string()
+        }
+    }
+}
+
+pub fn locale_name(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => "English",
+        Locale::ZhCN => "简体中文",
+        Locale::ZhTW => "繁體中文",
+        Locale::Ja => "日本語",
+        Locale::Ko => "한국어",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::path::Path;
+
+    fn keys(locale: Locale) -> BTreeSet<String> {
+        locales()[&locale].keys().cloned().collect()
+    }
+
+    // the string literals passed as the key of `tr(...)` in the sources
+    fn used_keys() -> BTreeSet<String> {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut keys = BTreeSet::new();
+
+        for entry in fs::read_dir(src).unwrap().flatten() {
+            let text = fs::read_to_string(entry.path()).unwrap_or_default();
+            for (i, _) in text.match_indices("tr(") {
+                let is_call = text[..i]
+                    .chars()
+                    .last()
+                    .is_none_or(|c| !c.is_alphanumeric() && c != '_');
+                if !is_call {
+                    continue;
+                }
+
+                let args = &text[i + 3..];
+                let args = &args[..args.find(')').unwrap_or(args.len())];
+                let mut parts = args.splitn(3, '"');
+                if let (Some(_), Some(key), Some(_)) = (parts.next(), parts.next(), parts.next()) {
+                    keys.insert(key.to_string());
+                }
+            }
+        }
+
+        keys
     }
 
-    let mut items: HashMap<&str, &str> = HashMap::new();
-    items.insert("出错", "Error");
-    items.insert("原因", "Reason");
-    items.insert("删除成功", "Delete success");
-    items.insert("删除失败", "Delete failed");
-    items.insert("添加成功", "Add success");
-    items.insert("添加失败", "Add failed");
-    items.insert("复制失败", "Copy failed");
-    items.insert("复制成功", "Copy success");
-    items.insert("清空失败", "Delete failed");
-    items.insert("清空成功", "Delete success");
-    items.insert("保存失败", "Save failed");
-    items.insert("保存成功", "Save success");
-    items.insert("重置成功", "Reset success");
-    items.insert("刷新成功", "Flush success");
-    items.insert("发送失败", "Send failed");
-    items.insert("下载成功", "Download success");
-    items.insert("下载失败", "Download failed");
-    items.insert("加载失败", "Load failed");
-    items.insert("密码错误", "Password Invalid");
-    items.insert("正在重试...", "Retrying...");
-    items.insert("正在下载...", "Downloading...");
-    items.insert("创建账户成功", "Create account success");
-    items.insert("创建账户失败", "Create account failed");
-    items.insert("密码错误", "Wrong password");
-    items.insert("修改密码成功", "Change password success");
-    items.insert("切换网络成功", "Switch network success");
-    items.insert("非法输入", "Invalid input");
-    items.insert("生成交易失败", "Generate transaction failed");
-    items.insert("发送交易成功", "Send transaction success");
-    items.insert("发送交易失败", "Send transaction failed");
-    items.insert("非法交易", "Invalid transaction");
-    items.insert("写入成功", "Write file success");
-    items.insert("取消成功", "Cancel success");
-    items.insert("解码成功", "Decode success");
-    items.insert("文件名为空", "File name is empty");
-    items.insert("非法文件", "Invalid file");
-    items.insert("行情", "Latest");
-    items.insert("热门", "Trending");
-    items.insert("原文链接", "Source Link");
-    items.insert("刷新", "Refresh");
-    items.insert("正在刷新", "Refreshing");
-    items.insert("关于", "About");
-    items.insert("在线", "Online");
-    items.insert("正忙", "Busy");
-    items.insert("空闲", "Idle");
-    items.insert("中文", "En");
-    items.insert("排名", "Rank");
-    items.insert("代币", "Symbol");
-    items.insert("价格", "Price");
-    items.insert("指数", "Stats");
-
-    items.insert("经济指数", "Economy");
-    items.insert("上证指数", "SZZS");
-    items.insert("深证成指", "SZSE");
-    items.insert("创业板指", "GEM");
-    items.insert("美元指数", "DXY");
-    items.insert("美国10年期国债收益率", "US 10Y Treasury Yield");
-    items.insert("标普500", "S&P500");
-    items.insert("离岸人民币", "CNH");
-
-    items.insert("加密指数", "Crypto");
-    items.insert("贪婪恐慌(今天/昨天)", "Greed Fear");
-    items.insert("加密总市值(USD)", "Total MarketCap(USD)");
-    items.insert("24h交易量(USD)", "24h Volumn(USD)");
-    items.insert("BTC市值占比", "BTC Domain");
-    items.insert("BTC油费(慢/正常/快)", "BTC Fee");
-    items.insert("ETH油费", "ETH Fee");
-
-
-
-    items.get(text).unwrap_or(&text).to_string()
+    #[test]
+    fn locale_files_parse() {
+        for (locale, text) in LOCALE_FILES {
+            assert!(serde_json::from_str::<Messages>(text).is_ok(), "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn locales_have_the_same_keys() {
+        let en = keys(Locale::En);
+        for locale in Locale::ALL {
+            let keys = keys(locale);
+            let missing = en.difference(&keys).collect::<Vec<_>>();
+            let extra = keys.difference(&en).collect::<Vec<_>>();
+            assert!(missing.is_empty(), "{locale:?} misses {missing:?}");
+            assert!(extra.is_empty(), "{locale:?} has unknown {extra:?}");
+        }
+    }
+
+    #[test]
+    fn used_keys_are_translated() {
+        let used = used_keys();
+        assert!(used.contains("latest"));
+
+        for locale in Locale::ALL {
+            let keys = keys(locale);
+            let missing = used.difference(&keys).collect::<Vec<_>>();
+            assert!(missing.is_empty(), "{locale:?} misses {missing:?}");
+        }
+    }
 }